@@ -0,0 +1,356 @@
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::games::cost::CostModel;
+use crate::games::heuristics::{linear_conflict_to, manhattan_distance};
+use crate::games::search::{self, SearchObserver, SearchOptions, SearchStats};
+use crate::games::sliding::{self, is_solvable, moves_to_string, Board, Move};
+
+// Number of expansions between two looks at the clock of a time limit
+const CLOCK_INTERVAL: usize = 1 << 12;
+
+// Define one board of an instance file, numbered by its id, with the seed it was drawn from
+#[derive(Clone, Debug)]
+pub struct Instance {
+    pub id: usize,
+    pub seed: Option<u64>,
+    pub board: Board,
+}
+
+// Load instances for rows x cols boards, one per line, as either
+//   an instance id followed by the tiles, with 0 as the blank,
+//   `seed <s>` for the board shuffled from seed s, or
+//   `seed <s> moves <n>` for the board n random moves from the goal drawn with seed s.
+// Seeded instances take their seed as id, so ids stay the same when the file is edited and
+// results can be matched with earlier runs. Two instances with the same id are an error. Empty
+// lines and lines starting with '#' are skipped.
+pub fn load_instances(path: &Path, rows: usize, cols: usize) -> io::Result<Vec<Instance>> {
+    let mut instances: Vec<Instance> = vec![];
+    let mut ids = HashSet::new();
+    for (number, line) in fs::read_to_string(path)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", number + 1, message));
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words[0] == "seed" {
+            let numbers = words.iter().skip(1).step_by(2).map(|word| word.parse::<u64>()).collect::<Result<Vec<u64>, _>>().map_err(|_| invalid("seed and moves must be numbers"))?;
+            let board = match (&words[..], &numbers[..]) {
+                ([_, _], &[seed]) => sliding::seeded_random_board(rows, cols, seed),
+                ([_, _, "moves", _], &[seed, moves]) => sliding::seeded_n_random_moves_from_goal(rows, cols, moves as usize, seed),
+                _ => return Err(invalid("expected `seed <s>` or `seed <s> moves <n>`")),
+            };
+            let id = usize::try_from(numbers[0]).map_err(|_| invalid("seed too large for an id"))?;
+            if !ids.insert(id) {
+                return Err(invalid(&format!("duplicate id {}", id)));
+            }
+            instances.push(Instance { id, seed: Some(numbers[0]), board });
+            continue;
+        }
+        let mut tiles = words.iter().map(|tile| tile.parse::<usize>()).collect::<Result<Vec<usize>, _>>().map_err(|_| invalid("tiles must be numbers"))?;
+        if tiles.len() != rows * cols + 1 {
+            return Err(invalid(&format!("expected an id followed by {} tiles", rows * cols)));
+        }
+        let id = tiles.remove(0);
+        if !ids.insert(id) {
+            return Err(invalid(&format!("duplicate id {}", id)));
+        }
+        let mut sorted = tiles.clone();
+        sorted.sort_unstable();
+        if sorted.len() != rows * cols || sorted.iter().enumerate().any(|(i, &tile)| tile != i) {
+            return Err(invalid(&format!("expected the tiles 0..{}", rows * cols - 1)));
+        }
+        let board = Board::from_tiles(rows, cols, tiles.into_iter().map(|tile| tile as u16).collect());
+        instances.push(Instance { id, seed: None, board });
+    }
+    Ok(instances)
+}
+
+// Define the output formats, chosen from the extension of the output file
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Format {
+    Csv,
+    JsonLines,
+}
+
+impl Format {
+    // JSON lines for .jsonl and .json files, CSV otherwise
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("jsonl" | "json") => Format::JsonLines,
+            _ => Format::Csv,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Algorithm {
+    AStar,
+    IdaStar,
+}
+
+// Define how every instance ended
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Status {
+    Solved,
+    NodeLimit,
+    TimeLimit,
+    Unsolvable,
+}
+
+impl Status {
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::Solved => "solved",
+            Status::NodeLimit => "node_limit",
+            Status::TimeLimit => "time_limit",
+            Status::Unsolvable => "unsolvable",
+        }
+    }
+}
+
+// Define a batch run: the search, the limits on every instance and the number of threads
+#[derive(Clone, Copy, Debug)]
+pub struct BatchOptions {
+    pub algorithm: Algorithm,
+    pub max_expanded: Option<usize>,
+    pub time_limit: Option<Duration>,
+    pub threads: usize,
+}
+
+// Define the outcome of one instance; the counters of a stopped search are the ones reached
+// when it stopped
+#[derive(Clone, Debug)]
+pub struct BatchResult {
+    pub id: usize,
+    pub seed: Option<u64>,
+    pub moves: Option<Vec<Move>>,
+    pub stats: SearchStats,
+    pub elapsed: Duration,
+    pub status: Status,
+}
+
+impl BatchResult {
+    fn csv(&self) -> String {
+        let seed = self.seed.map_or(String::new(), |seed| seed.to_string());
+        let (length, moves) = self.moves.as_ref().map_or((String::new(), String::new()), |moves| (moves.len().to_string(), moves_to_string(moves)));
+        format!(
+            "{},{},{},{},{},{},{:.3},{}",
+            self.id,
+            seed,
+            length,
+            moves,
+            self.stats.expanded,
+            self.stats.generated,
+            self.elapsed.as_secs_f64() * 1000.0,
+            self.status.name()
+        )
+    }
+
+    fn json(&self) -> String {
+        let seed = self.seed.map_or("null".to_string(), |seed| seed.to_string());
+        let (length, moves) = self.moves.as_ref().map_or(("null".to_string(), "null".to_string()), |moves| (moves.len().to_string(), format!("\"{}\"", moves_to_string(moves))));
+        format!(
+            "{{\"id\":{},\"seed\":{},\"length\":{},\"moves\":{},\"expanded\":{},\"generated\":{},\"millis\":{:.3},\"status\":\"{}\"}}",
+            self.id,
+            seed,
+            length,
+            moves,
+            self.stats.expanded,
+            self.stats.generated,
+            self.elapsed.as_secs_f64() * 1000.0,
+            self.status.name()
+        )
+    }
+}
+
+const CSV_HEADER: &str = "id,seed,length,moves,expanded,generated,millis,status";
+
+// Read the ids of the instances an earlier run recorded as solved in `path`, none when the
+// file does not exist yet
+pub fn solved_ids(path: &Path, format: Format) -> io::Result<HashSet<usize>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(HashSet::new()),
+        Err(error) => return Err(error),
+    };
+    let solved = text.lines().filter_map(|line| match format {
+        Format::Csv => {
+            let fields: Vec<&str> = line.split(',').collect();
+            (fields.last() == Some(&Status::Solved.name())).then(|| fields[0].parse().ok()).flatten()
+        }
+        Format::JsonLines => {
+            let status = format!("\"status\":\"{}\"", Status::Solved.name());
+            let id = line.strip_prefix("{\"id\":")?.split(',').next()?;
+            line.contains(&status).then(|| id.parse().ok()).flatten()
+        }
+    });
+    Ok(solved.collect())
+}
+
+// Define an observer counting the boards a search expands and generates, stopping it at the
+// node or time limit of its instance
+struct Limiter {
+    max_expanded: Option<usize>,
+    deadline: Option<Instant>,
+    stats: SearchStats,
+    hit: Option<Status>,
+}
+
+impl SearchObserver for Limiter {
+    fn on_expand(&mut self, _board: &Board, _g: u32, _h: u32) {
+        self.stats.expanded += 1;
+    }
+
+    fn on_generate(&mut self, _board: &Board, _g: u32, _h: u32) {
+        self.stats.generated += 1;
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if self.hit.is_none() {
+            if self.max_expanded.is_some_and(|max| self.stats.expanded >= max) {
+                self.hit = Some(Status::NodeLimit);
+            } else if self.stats.expanded.is_multiple_of(CLOCK_INTERVAL) && self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                self.hit = Some(Status::TimeLimit);
+            }
+        }
+        self.hit.is_some()
+    }
+}
+
+// Solve one instance with Manhattan distance plus linear conflicts towards the goal with the
+// blank last
+fn solve(instance: &Instance, options: &BatchOptions) -> BatchResult {
+    let now = Instant::now();
+    let (rows, cols) = (instance.board.rows(), instance.board.cols());
+    let goal = Board::goal(rows, cols);
+    let result = |moves, stats, status| BatchResult { id: instance.id, seed: instance.seed, moves, stats, elapsed: now.elapsed(), status };
+    if !is_solvable(&instance.board) {
+        return result(None, SearchStats::default(), Status::Unsolvable);
+    }
+    let heuristic = |board: &Board| manhattan_distance(board) + linear_conflict_to(board, &goal);
+    let mut limiter = Limiter { max_expanded: options.max_expanded, deadline: options.time_limit.map(|limit| now + limit), stats: SearchStats::default(), hit: None };
+    let search_options = SearchOptions::default();
+    let solution = match options.algorithm {
+        Algorithm::AStar => search::a_star_search_observed(&instance.board, &goal, &heuristic, &CostModel::Unit, &search_options, &mut limiter),
+        Algorithm::IdaStar => search::ida_star_search_observed(&instance.board, &goal, &heuristic, &CostModel::Unit, &search_options, &mut limiter),
+    };
+    match solution {
+        Some(solution) => result(Some(solution.moves), solution.stats, Status::Solved),
+        None => result(None, limiter.stats, limiter.hit.unwrap_or(Status::Unsolvable)),
+    }
+}
+
+// Define the counts of a batch run
+#[derive(Clone, Copy, Default, Debug)]
+pub struct BatchSummary {
+    pub solved: usize,
+    pub unsolved: usize,
+    // Instances already solved in the output file
+    pub skipped: usize,
+}
+
+// Solve the instances on `threads` threads and append every result to `output` as soon as it
+// is known, in the order they finish, flushing after every line. Instances the output already
+// records as solved are skipped, the others are solved again and get a new line. `on_result`
+// is called with every result after it was written.
+pub fn run_batch(instances: &[Instance], output: &Path, options: &BatchOptions, on_result: &mut dyn FnMut(&BatchResult)) -> io::Result<BatchSummary> {
+    let format = Format::from_path(output);
+    let done = solved_ids(output, format)?;
+    let pending: Vec<&Instance> = instances.iter().filter(|instance| !done.contains(&instance.id)).collect();
+    let mut summary = BatchSummary { skipped: instances.len() - pending.len(), ..BatchSummary::default() };
+
+    let is_new = fs::metadata(output).map_or(true, |metadata| metadata.len() == 0);
+    let mut file = OpenOptions::new().create(true).append(true).open(output)?;
+    if is_new && format == Format::Csv {
+        writeln!(file, "{}", CSV_HEADER)?;
+    }
+
+    let next = AtomicUsize::new(0);
+    let (sender, results) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..options.threads.clamp(1, pending.len().max(1)) {
+            let sender = sender.clone();
+            let (next, pending) = (&next, &pending);
+            scope.spawn(move || {
+                while let Some(instance) = pending.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    if sender.send(solve(instance, options)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        for result in results {
+            let line = match format {
+                Format::Csv => result.csv(),
+                Format::JsonLines => result.json(),
+            };
+            writeln!(file, "{}", line)?;
+            file.flush()?;
+            if result.status == Status::Solved {
+                summary.solved += 1;
+            } else {
+                summary.unsolved += 1;
+            }
+            on_result(&result);
+        }
+        Ok(summary)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::test_support::temporary_path;
+
+    fn instances() -> Vec<Instance> {
+        let solvable = |seed| Instance { id: seed as usize, seed: Some(seed), board: sliding::seeded_n_random_moves_from_goal(3, 3, 20, seed) };
+        // Swapping two tiles makes a board unsolvable
+        let unsolvable = Instance { id: 100, seed: None, board: Board::from_tiles(3, 3, vec![2, 1, 3, 4, 5, 6, 7, 8, 0]) };
+        vec![solvable(1), solvable(2), unsolvable, solvable(3)]
+    }
+
+    fn written_ids(format: &str) {
+        let output = temporary_path(&format!("batch_results.{}", format));
+        let options = BatchOptions { algorithm: Algorithm::IdaStar, max_expanded: None, time_limit: None, threads: 2 };
+        let summary = run_batch(&instances(), &output, &options, &mut |_| {}).unwrap();
+        assert_eq!((summary.solved, summary.unsolved, summary.skipped), (3, 1, 0));
+        assert_eq!(solved_ids(&output, Format::from_path(&output)).unwrap(), HashSet::from([1, 2, 3]));
+
+        // A second run skips the solved instances and only tries the unsolvable one again
+        let summary = run_batch(&instances(), &output, &options, &mut |_| {}).unwrap();
+        assert_eq!((summary.solved, summary.unsolved, summary.skipped), (0, 1, 3));
+        fs::remove_file(&output).unwrap();
+    }
+
+    #[test]
+    fn solved_ids_reads_csv_results() {
+        written_ids("csv");
+    }
+
+    #[test]
+    fn solved_ids_reads_json_lines_results() {
+        written_ids("jsonl");
+    }
+
+    #[test]
+    fn load_instances_rejects_missing_and_duplicate_ids() {
+        let file = temporary_path("batch_instances.txt");
+        fs::write(&file, "# id and tiles\n7 1 2 3 4 5 6 7 0 8\nseed 3\nseed 4 moves 10\n").unwrap();
+        let ids: Vec<usize> = load_instances(&file, 3, 3).unwrap().iter().map(|instance| instance.id).collect();
+        assert_eq!(ids, vec![7, 3, 4]);
+        for text in ["1 2 3 4 5 6 7 0 8\n", "3 1 2 3 4 5 6 7 0 8\nseed 3\n", "seed 5\nseed 5 moves 10\n"] {
+            fs::write(&file, text).unwrap();
+            assert_eq!(load_instances(&file, 3, 3).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
+        fs::remove_file(&file).unwrap();
+    }
+}
@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::games::cost::CostModel;
+use crate::games::observers::Broadcast;
+use crate::games::search::{self, IdaState, SearchObserver, SearchOptions, SearchStats, Solution};
+use crate::games::sliding::{moves_to_string, Board, Move, MOVES};
+
+// Number of expansions between two looks at the clock
+const CLOCK_INTERVAL: usize = 1 << 16;
+
+// Define the saved state of an IDA* run: the boards, the bound of the current iteration, the
+// smallest f-value above it met so far, the path from the start to the board being expanded
+// and the counters. Every subtree left of the path has been searched, so a resumed run replays
+// the path and carries on from the board at its end. A finished run keeps its solution as the
+// path.
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    pub start: Board,
+    pub goal: Board,
+    pub bound: u32,
+    pub next_bound: Option<u32>,
+    pub path: Vec<Move>,
+    pub stats: SearchStats,
+    pub solved: bool,
+}
+
+fn parse_move(c: char) -> Option<Move> {
+    MOVES.into_iter().find(|m| m.to_char() == c)
+}
+
+fn tiles_text(board: &Board) -> String {
+    let tiles: Vec<String> = board.tiles().iter().map(|tile| tile.to_string()).collect();
+    tiles.join(" ")
+}
+
+impl Checkpoint {
+    // Read a checkpoint written by `save`, checking that its path can be played from its start
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), message));
+        let text = fs::read_to_string(path)?;
+        let mut fields = HashMap::new();
+        for line in text.lines() {
+            if let Some((key, value)) = line.split_once(' ') {
+                fields.insert(key, value.trim());
+            } else if !line.trim().is_empty() {
+                fields.insert(line.trim(), "");
+            }
+        }
+        let field = |key: &str| fields.get(key).copied().ok_or_else(|| invalid(&format!("missing {}", key)));
+        let numbers = |key: &str| -> io::Result<Vec<u64>> {
+            field(key)?.split_whitespace().map(|number| number.parse().map_err(|_| invalid(&format!("bad {}", key)))).collect()
+        };
+        let size = numbers("size")?;
+        let [rows, cols] = size[..] else { return Err(invalid("bad size")) };
+        let (rows, cols) = (rows as usize, cols as usize);
+        let board = |key: &str| -> io::Result<Board> {
+            let tiles: Vec<u16> = numbers(key)?.into_iter().map(|tile| tile as u16).collect();
+            let mut sorted = tiles.clone();
+            sorted.sort_unstable();
+            if sorted.len() != rows * cols || sorted.iter().enumerate().any(|(i, &tile)| tile as usize != i) {
+                return Err(invalid(&format!("bad {}", key)));
+            }
+            Ok(Board::from_tiles(rows, cols, tiles))
+        };
+        let (start, goal) = (board("start")?, board("goal")?);
+        let bound = field("bound")?.parse().map_err(|_| invalid("bad bound"))?;
+        let next_bound = match field("next")? {
+            "none" => None,
+            next => Some(next.parse().map_err(|_| invalid("bad next"))?),
+        };
+        let path = field("path")?.chars().map(parse_move).collect::<Option<Vec<Move>>>().ok_or_else(|| invalid("bad path"))?;
+        let mut board = start.clone();
+        if !path.iter().all(|&m| board.apply(m)) {
+            return Err(invalid("path leaves the board"));
+        }
+        let counters = numbers("stats")?;
+        let [expanded, generated, reopened, inconsistent_edges, pathmax_updates] = counters[..] else { return Err(invalid("bad stats")) };
+        let stats = SearchStats {
+            expanded: expanded as usize,
+            generated: generated as usize,
+            reopened: reopened as usize,
+            inconsistent_edges: inconsistent_edges as usize,
+            pathmax_updates: pathmax_updates as usize,
+        };
+        let solved = match field("state")? {
+            "solved" => true,
+            "running" => false,
+            _ => return Err(invalid("bad state")),
+        };
+        Ok(Self { start, goal, bound, next_bound, path, stats, solved })
+    }
+
+    // Write the checkpoint next to the old one and swap it in, so a crash leaves a valid one
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let stats = &self.stats;
+        let text = format!(
+            "size {} {}\nstart {}\ngoal {}\nbound {}\nnext {}\npath {}\nstats {} {} {} {} {}\nstate {}\n",
+            self.start.rows(),
+            self.start.cols(),
+            tiles_text(&self.start),
+            tiles_text(&self.goal),
+            self.bound,
+            self.next_bound.map_or("none".to_string(), |next| next.to_string()),
+            moves_to_string(&self.path),
+            stats.expanded,
+            stats.generated,
+            stats.reopened,
+            stats.inconsistent_edges,
+            stats.pathmax_updates,
+            if self.solved { "solved" } else { "running" }
+        );
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, text)?;
+        fs::rename(temporary, path)
+    }
+}
+
+// Define where a resumable run keeps its checkpoint and how long it may run between two saves
+#[derive(Clone, Copy, Debug)]
+pub struct SaveOptions<'a> {
+    pub file: &'a Path,
+    pub interval: Duration,
+}
+
+// Define the observer saving a run to its file: at the start of every iteration, before the
+// first expansion after `interval` has passed and on the solution. The first error stops the
+// search and is kept to be returned.
+struct Saver<'a> {
+    start: &'a Board,
+    goal: &'a Board,
+    save: SaveOptions<'a>,
+    saved: Instant,
+    bound: u32,
+    error: Option<io::Error>,
+}
+
+impl Saver<'_> {
+    fn save(&mut self, state: &IdaState, solved: bool) {
+        let checkpoint = Checkpoint {
+            start: self.start.clone(),
+            goal: self.goal.clone(),
+            bound: state.bound,
+            next_bound: state.next_bound,
+            path: state.path.clone(),
+            stats: state.stats,
+            solved,
+        };
+        if let Err(error) = checkpoint.save(self.save.file) {
+            self.error.get_or_insert(error);
+        }
+        self.saved = Instant::now();
+    }
+}
+
+impl SearchObserver for Saver<'_> {
+    fn on_iteration(&mut self, bound: u32, _stats: &SearchStats) {
+        self.bound = bound;
+    }
+
+    fn on_snapshot(&mut self, state: &IdaState) {
+        let due = state.stats.expanded.is_multiple_of(CLOCK_INTERVAL) && self.saved.elapsed() >= self.save.interval;
+        if state.path.is_empty() || due {
+            self.save(state, false);
+        }
+    }
+
+    fn on_solution(&mut self, moves: &[Move], stats: &SearchStats) {
+        let state = IdaState { bound: self.bound, next_bound: None, path: moves.to_vec(), stats: *stats };
+        self.save(&state, true);
+    }
+
+    fn should_stop(&mut self) -> bool {
+        self.error.is_some()
+    }
+}
+
+// Define IDA* that saves its state to the file of `save` every interval or so and at the start
+// of every iteration. When the file holds a checkpoint for the same boards the search resumes from it,
+// the counters carrying on from the saved ones; the heuristic, costs and options must be the
+// same as in the saved run for the result to be optimal. A killed run loses at most the work
+// since the last save. Every event also goes to `observer`, which can stop the search.
+pub fn resumable_ida_star_search(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32, costs: &CostModel, options: &SearchOptions, save: SaveOptions, observer: &mut dyn SearchObserver) -> io::Result<Option<Solution>> {
+    let file = save.file;
+    let state = match Checkpoint::load(file) {
+        Ok(checkpoint) if checkpoint.start == *start && checkpoint.goal == *goal => {
+            if checkpoint.solved {
+                return Ok(Some(Solution { moves: checkpoint.path, stats: checkpoint.stats }));
+            }
+            IdaState { bound: checkpoint.bound, next_bound: checkpoint.next_bound, path: checkpoint.path, stats: checkpoint.stats }
+        }
+        Ok(_) => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} belongs to another search", file.display()))),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => IdaState { bound: heuristic(start), ..IdaState::default() },
+        Err(error) => return Err(error),
+    };
+    let mut saver = Saver { start, goal, save, saved: Instant::now(), bound: state.bound, error: None };
+    let solution = search::resume_ida_star_search(start, goal, heuristic, costs, options, state, &mut Broadcast(vec![&mut saver, observer]));
+    match saver.error {
+        Some(error) => Err(error),
+        None => Ok(solution),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::heuristics::{linear_conflict_to, manhattan_distance, weighted_manhattan_distance_to};
+    use crate::games::search::NoObserver;
+    use crate::games::sliding;
+    use crate::games::test_support::temporary_path;
+
+    // Define an observer keeping a copy of the checkpoint file after every save
+    struct Snapshots<'a> {
+        file: &'a Path,
+        texts: Vec<String>,
+    }
+
+    impl SearchObserver for Snapshots<'_> {
+        fn on_snapshot(&mut self, state: &IdaState) {
+            // With no interval between saves the file is written exactly at these points
+            if state.path.is_empty() || state.stats.expanded.is_multiple_of(CLOCK_INTERVAL) {
+                self.texts.push(fs::read_to_string(self.file).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let file = temporary_path("checkpoint_round_trip.txt");
+        let start = sliding::seeded_n_random_moves_from_goal(4, 4, 30, 1);
+        let stats = SearchStats { expanded: 123456, generated: 234567, reopened: 3, inconsistent_edges: 4, pathmax_updates: 5 };
+        let path = vec![Move::Up, Move::Left, Move::Down, Move::Right];
+        let checkpoint = Checkpoint { start: start.clone(), goal: Board::goal(4, 4), bound: 42, next_bound: Some(44), path: path.clone(), stats, solved: false };
+        checkpoint.save(&file).unwrap();
+        let loaded = Checkpoint::load(&file).unwrap();
+        fs::remove_file(&file).unwrap();
+        assert_eq!(loaded.start, start);
+        assert_eq!(loaded.goal, Board::goal(4, 4));
+        assert_eq!((loaded.bound, loaded.next_bound, loaded.solved), (42, Some(44), false));
+        assert_eq!(loaded.path, path);
+        let counters = |stats: &SearchStats| (stats.expanded, stats.generated, stats.reopened, stats.inconsistent_edges, stats.pathmax_updates);
+        assert_eq!(counters(&loaded.stats), counters(&stats));
+    }
+
+    // Run the search saving as often as possible, then resume from every checkpoint written
+    fn assert_resumes(start: &Board, heuristic: &dyn Fn(&Board) -> u32, costs: &CostModel, name: &str) {
+        let file = temporary_path(name);
+        let goal = Board::goal(start.rows(), start.cols());
+        let options = SearchOptions::default();
+        let mut snapshots = Snapshots { file: &file, texts: vec![] };
+        let full = resumable_ida_star_search(start, &goal, heuristic, costs, &options, SaveOptions { file: &file, interval: Duration::ZERO }, &mut snapshots).unwrap().unwrap();
+        let reference = search::ida_star_search_with_costs(start, &goal, heuristic, costs, &mut |_, _| {}).unwrap();
+        assert_eq!(full.moves, reference.moves);
+        assert_eq!(full.stats.expanded, reference.stats.expanded);
+        assert!(Checkpoint::load(&file).unwrap().solved);
+
+        // Resuming from every iteration start would repeat most of the search many times, so
+        // only the first and the last are tried along with every save inside an iteration
+        let mut inside_iteration = false;
+        let last = snapshots.texts.len() - 1;
+        for (i, text) in snapshots.texts.iter().enumerate() {
+            fs::write(&file, text).unwrap();
+            let inside = !Checkpoint::load(&file).unwrap().path.is_empty();
+            if !inside && i != 0 && i != last {
+                continue;
+            }
+            inside_iteration |= inside;
+            let resumed = resumable_ida_star_search(start, &goal, heuristic, costs, &options, SaveOptions { file: &file, interval: Duration::from_secs(3600) }, &mut NoObserver).unwrap().unwrap();
+            assert_eq!(resumed.moves, full.moves);
+            assert_eq!(resumed.stats.expanded, full.stats.expanded);
+            assert_eq!(resumed.stats.generated, full.stats.generated);
+        }
+        assert!(inside_iteration);
+        fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn resumed_run_matches_uninterrupted_run() {
+        let goal = Board::goal(4, 4);
+        let heuristic = |board: &Board| manhattan_distance(board) + linear_conflict_to(board, &goal);
+        assert_resumes(&sliding::seeded_n_random_moves_from_goal(4, 4, 60, 3), &heuristic, &CostModel::Unit, "checkpoint_resume.txt");
+    }
+
+    #[test]
+    fn resumed_run_keeps_the_cost_model() {
+        let goal = Board::goal(4, 4);
+        let heuristic = |board: &Board| weighted_manhattan_distance_to(board, &goal, &CostModel::TileValue);
+        assert_resumes(&sliding::seeded_n_random_moves_from_goal(4, 4, 60, 4), &heuristic, &CostModel::TileValue, "checkpoint_costs.txt");
+    }
+
+    #[test]
+    fn errors_and_other_searches_are_reported() {
+        let goal = Board::goal(3, 3);
+        let start = sliding::seeded_random_board(3, 3, 1);
+        let file = temporary_path("checkpoint_other.txt");
+        resumable_ida_star_search(&start, &goal, &manhattan_distance, &CostModel::Unit, &SearchOptions::default(), SaveOptions { file: &file, interval: Duration::ZERO }, &mut NoObserver).unwrap().unwrap();
+        let other = sliding::seeded_random_board(3, 3, 2);
+        let error = resumable_ida_star_search(&other, &goal, &manhattan_distance, &CostModel::Unit, &SearchOptions::default(), SaveOptions { file: &file, interval: Duration::ZERO }, &mut NoObserver).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        fs::remove_file(&file).unwrap();
+
+        // A file that cannot be written stops the search with the error
+        let missing = temporary_path("checkpoint_missing").join("checkpoint.txt");
+        assert!(resumable_ida_star_search(&start, &goal, &manhattan_distance, &CostModel::Unit, &SearchOptions::default(), SaveOptions { file: &missing, interval: Duration::ZERO }, &mut NoObserver).is_err());
+    }
+}
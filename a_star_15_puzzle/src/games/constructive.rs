@@ -0,0 +1,316 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+use crate::games::heuristics::manhattan_distance;
+use crate::games::search;
+use crate::games::sliding::{is_solvable, Board, Move, MOVES};
+
+// Solve a board of any size the way a person would: fix the top row or the left column, whichever
+// is longer, and repeat on the remaining board until at most 3x3 is left, which is finished
+// optimally with A*.
+// The solution is valid but not optimal. Returns None for unsolvable boards.
+pub fn solve(start: &Board) -> Option<Vec<Move>> {
+    if !is_solvable(start) {
+        return None;
+    }
+    let (rows, cols) = (start.rows(), start.cols());
+    // Boards one tile wide cannot be solved line by line, but their state space is tiny
+    if (rows <= 3 && cols <= 3) || rows == 1 || cols == 1 {
+        return search::a_star_search(start, &Board::goal(rows, cols), &manhattan_distance).map(|solution| solution.moves);
+    }
+    let mut solver = Solver {
+        board: start.clone(),
+        rows,
+        cols,
+        locked: vec![false; rows * cols],
+        moves: vec![],
+    };
+    let mut top = 0;
+    let mut left = 0;
+    while rows - top > 3 || cols - left > 3 {
+        if rows - top >= cols - left {
+            solver.solve_row(top, left)?;
+            top += 1;
+        } else {
+            solver.solve_column(left, top)?;
+            left += 1;
+        }
+    }
+    solver.finish(top, left)?;
+    Some(solver.moves)
+}
+
+// Define the cell reached from `cell` by moving in the given direction
+fn step(rows: usize, cols: usize, cell: usize, m: Move) -> Option<usize> {
+    let (row, col) = (cell / cols, cell % cols);
+    match m {
+        Move::Up if row > 0 => Some(cell - cols),
+        Move::Down if row + 1 < rows => Some(cell + cols),
+        Move::Left if col > 0 => Some(cell - 1),
+        Move::Right if col + 1 < cols => Some(cell + 1),
+        _ => None,
+    }
+}
+
+// Define the breadth-first search over cells used to route tiles and the blank
+fn route(rows: usize, cols: usize, from: usize, is_target: &dyn Fn(usize) -> bool, is_blocked: &dyn Fn(usize) -> bool) -> Option<Vec<(Move, usize)>> {
+    let mut parent: Vec<Option<(usize, Move)>> = vec![None; rows * cols];
+    let mut queue = VecDeque::new();
+    queue.push_back(from);
+    parent[from] = Some((from, Move::Up));
+    while let Some(cell) = queue.pop_front() {
+        if is_target(cell) {
+            let mut path = vec![];
+            let mut current = cell;
+            while current != from {
+                let (previous, m) = parent[current].unwrap();
+                path.push((m, current));
+                current = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for m in MOVES {
+            if let Some(next) = step(rows, cols, cell, m) {
+                if !is_blocked(next) && parent[next].is_none() {
+                    parent[next] = Some((cell, m));
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    None
+}
+
+// Positions of the two tiles being placed together with the blank
+type PairState = (usize, usize, usize);
+
+struct Solver {
+    board: Board,
+    rows: usize,
+    cols: usize,
+    locked: Vec<bool>,
+    moves: Vec<Move>,
+}
+
+impl Solver {
+    fn position(&self, tile: u16) -> usize {
+        self.board.tiles().iter().position(|&t| t == tile).expect("tile is on the board")
+    }
+
+    fn blank(&self) -> usize {
+        let (row, col) = self.board.blank();
+        row * self.cols + col
+    }
+
+    // Tile that belongs on the given cell in the goal state
+    fn goal_tile(&self, row: usize, col: usize) -> u16 {
+        (row * self.cols + col + 1) as u16
+    }
+
+    fn push(&mut self, m: Move) {
+        self.board.apply(m);
+        self.moves.push(m);
+    }
+
+    // Move the blank to the target cell without touching locked cells or the avoided one
+    fn move_blank_to(&mut self, target: usize, avoid: usize) -> Option<()> {
+        let locked = &self.locked;
+        let path = route(self.rows, self.cols, self.blank(), &|cell| cell == target, &|cell| locked[cell] || cell == avoid)?;
+        for (m, _) in path {
+            self.push(m);
+        }
+        Some(())
+    }
+
+    // Macro-operator: walk a tile step by step until it stands on one of the target cells,
+    // bringing the blank in front of it before every step
+    fn move_tile_into(&mut self, tile: u16, is_target: &dyn Fn(usize) -> bool) -> Option<()> {
+        loop {
+            let position = self.position(tile);
+            if is_target(position) {
+                return Some(());
+            }
+            let locked = &self.locked;
+            let path = route(self.rows, self.cols, position, is_target, &|cell| locked[cell])?;
+            let (m, next) = path[0];
+            self.move_blank_to(next, position)?;
+            self.push(m.opposite());
+        }
+    }
+
+    // Place the last two tiles of a row or column. Placing them one by one can trap the second
+    // tile behind the first, so both are brought into a small window first and then arranged
+    // together with a breadth-first search over their positions and the blank.
+    fn place_pair(&mut self, a: u16, a_target: usize, b: u16, b_target: usize, window: &[usize]) -> Option<()> {
+        let in_window = |cell: usize| window.contains(&cell);
+        if self.position(a) != a_target || self.position(b) != b_target {
+            // Holding the first tile still can corner the blank, so swap the order and retry
+            let mut gathered = false;
+            for (first, second) in [(a, b), (b, a), (a, b), (b, a)] {
+                if self.move_tile_into(first, &in_window).is_none() {
+                    continue;
+                }
+                let first_position = self.position(first);
+                self.locked[first_position] = true;
+                let result = self.move_tile_into(second, &in_window);
+                self.locked[first_position] = false;
+                if result.is_some() {
+                    gathered = true;
+                    break;
+                }
+            }
+            // On boards only two or three tiles deep the window can be too tight to gather both
+            // tiles, or the gathered tiles can wall the blank off from it, so search over every
+            // free cell instead. The blank is always free, so it never has to enter that window.
+            let free: Vec<usize> = (0..self.rows * self.cols).filter(|&cell| !self.locked[cell]).collect();
+            let mut window = if gathered { window } else { &free[..] };
+            if !window.contains(&self.blank()) {
+                let (a_position, b_position) = (self.position(a), self.position(b));
+                let locked = &self.locked;
+                let path = route(
+                    self.rows,
+                    self.cols,
+                    self.blank(),
+                    &|cell| window.contains(&cell) && cell != a_position && cell != b_position,
+                    &|cell| locked[cell] || cell == a_position || cell == b_position,
+                );
+                match path {
+                    Some(path) => path.into_iter().for_each(|(m, _)| self.push(m)),
+                    None => window = &free,
+                }
+            }
+            let in_window = |cell: usize| window.contains(&cell);
+
+            let start = (self.position(a), self.position(b), self.blank());
+            let mut parent: HashMap<PairState, (PairState, Move)> = HashMap::new();
+            let mut queue = VecDeque::new();
+            queue.push_back(start);
+            parent.insert(start, (start, Move::Up));
+            let mut goal = None;
+            while let Some(state) = queue.pop_front() {
+                if state.0 == a_target && state.1 == b_target {
+                    goal = Some(state);
+                    break;
+                }
+                let (a_cell, b_cell, blank) = state;
+                for m in MOVES {
+                    if let Some(next) = step(self.rows, self.cols, blank, m).filter(|&cell| in_window(cell)) {
+                        let next_state = (
+                            if a_cell == next { blank } else { a_cell },
+                            if b_cell == next { blank } else { b_cell },
+                            next,
+                        );
+                        if let Entry::Vacant(entry) = parent.entry(next_state) {
+                            entry.insert((state, m));
+                            queue.push_back(next_state);
+                        }
+                    }
+                }
+            }
+            let mut current = goal?;
+            let mut path = vec![];
+            while current != start {
+                let (previous, m) = parent[&current];
+                path.push(m);
+                current = previous;
+            }
+            for m in path.into_iter().rev() {
+                self.push(m);
+            }
+        }
+        self.locked[a_target] = true;
+        self.locked[b_target] = true;
+        Some(())
+    }
+
+    fn solve_row(&mut self, row: usize, left: usize) -> Option<()> {
+        let cols = self.cols;
+        for col in left..cols - 2 {
+            let target = row * cols + col;
+            self.move_tile_into(self.goal_tile(row, col), &|cell| cell == target)?;
+            self.locked[target] = true;
+        }
+        let window: Vec<usize> = (row..(row + 3).min(self.rows))
+            .flat_map(|r| (left.max(cols.saturating_sub(3))..cols).map(move |c| r * cols + c))
+            .filter(|&cell| !self.locked[cell])
+            .collect();
+        self.place_pair(
+            self.goal_tile(row, cols - 2),
+            row * cols + cols - 2,
+            self.goal_tile(row, cols - 1),
+            row * cols + cols - 1,
+            &window,
+        )
+    }
+
+    fn solve_column(&mut self, col: usize, top: usize) -> Option<()> {
+        let (rows, cols) = (self.rows, self.cols);
+        for row in top..rows - 2 {
+            let target = row * cols + col;
+            self.move_tile_into(self.goal_tile(row, col), &|cell| cell == target)?;
+            self.locked[target] = true;
+        }
+        let window: Vec<usize> = (top.max(rows.saturating_sub(3))..rows)
+            .flat_map(|r| (col..(col + 3).min(cols)).map(move |c| r * cols + c))
+            .filter(|&cell| !self.locked[cell])
+            .collect();
+        self.place_pair(
+            self.goal_tile(rows - 2, col),
+            (rows - 2) * cols + col,
+            self.goal_tile(rows - 1, col),
+            (rows - 1) * cols + col,
+            &window,
+        )
+    }
+
+    // Finish the remaining region of at most 3x3 optimally by relabelling it as a small puzzle
+    fn finish(&mut self, top: usize, left: usize) -> Option<()> {
+        let (rows, cols) = (self.rows, self.cols);
+        let (height, width) = (rows - top, cols - left);
+        let mut tiles = vec![];
+        for row in top..rows {
+            for col in left..cols {
+                let tile = self.board.tiles()[row * cols + col];
+                if tile == 0 {
+                    tiles.push(0);
+                } else {
+                    let goal = tile as usize - 1;
+                    tiles.push(((goal / cols - top) * width + goal % cols - left + 1) as u16);
+                }
+            }
+        }
+        let solution = search::a_star_search(&Board::from_tiles(height, width, tiles), &Board::goal(height, width), &manhattan_distance)?;
+        for m in solution.moves {
+            self.push(m);
+        }
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::sliding;
+    use crate::games::test_support::played;
+
+    fn assert_solves(rows: usize, cols: usize, seeds: u64) {
+        for seed in 0..seeds {
+            let start = sliding::seeded_random_board(rows, cols, seed);
+            let moves = solve(&start).unwrap_or_else(|| panic!("no solution for {}x{} seed {}", rows, cols, seed));
+            assert_eq!(played(&start, &moves), Some(Board::goal(rows, cols)), "{}x{} seed {}", rows, cols, seed);
+        }
+    }
+
+    #[test]
+    fn solves_boards_two_lines_deep() {
+        assert_solves(2, 6, 100);
+        assert_solves(6, 2, 100);
+    }
+
+    #[test]
+    fn solves_large_boards() {
+        assert_solves(10, 10, 20);
+        assert_solves(20, 20, 1);
+    }
+}
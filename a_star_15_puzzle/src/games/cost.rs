@@ -0,0 +1,65 @@
+use crate::games::sliding::{Board, Move};
+
+// Define what a move costs: the blank moves one tile, and the cost depends on that tile
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CostModel {
+    // Every move costs 1
+    Unit,
+    // Moving tile t costs t
+    TileValue,
+    // Moving tile t costs table[t], the entry for the blank is unused
+    Table(Vec<u32>),
+}
+
+impl CostModel {
+    pub fn tile_cost(&self, tile: u16) -> u32 {
+        match self {
+            CostModel::Unit => 1,
+            CostModel::TileValue => tile as u32,
+            CostModel::Table(table) => table[tile as usize],
+        }
+    }
+
+    // Cost of sliding the blank of `board` in the given direction
+    pub fn move_cost(&self, board: &Board, m: Move) -> u32 {
+        board.tile_moved_by(m).map_or(0, |tile| self.tile_cost(tile))
+    }
+
+    // Total cost of a move sequence played from `start`
+    pub fn path_cost(&self, start: &Board, moves: &[Move]) -> u32 {
+        let mut board = start.clone();
+        let mut cost = 0;
+        for &m in moves {
+            cost += self.move_cost(&board, m);
+            board.apply(m);
+        }
+        cost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_cost_the_tile_they_slide() {
+        let board = Board::from([[1, 2, 3], [4, 0, 6], [7, 5, 8]]);
+        let table = CostModel::Table(vec![0, 10, 20, 30, 40, 50, 60, 70, 80]);
+        for (m, tile) in [(Move::Up, 2), (Move::Down, 5), (Move::Left, 4), (Move::Right, 6)] {
+            assert_eq!(CostModel::Unit.move_cost(&board, m), 1);
+            assert_eq!(CostModel::TileValue.move_cost(&board, m), tile);
+            assert_eq!(table.move_cost(&board, m), 10 * tile);
+        }
+        let corner = Board::goal(3, 3);
+        assert_eq!(CostModel::TileValue.move_cost(&corner, Move::Down), 0);
+    }
+
+    #[test]
+    fn path_cost_adds_every_move() {
+        let start = Board::from([[1, 2, 3], [4, 5, 6], [0, 7, 8]]);
+        let moves = [Move::Right, Move::Right, Move::Up];
+        assert_eq!(CostModel::Unit.path_cost(&start, &moves), 3);
+        assert_eq!(CostModel::TileValue.path_cost(&start, &moves), 7 + 8 + 6);
+        assert_eq!(CostModel::TileValue.path_cost(&start, &[]), 0);
+    }
+}
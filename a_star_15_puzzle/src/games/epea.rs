@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use crate::games::open_list::{BucketOpenList, TieBreaking};
+use crate::games::pattern_database::{cells_of, AdditivePatternDatabase};
+use crate::games::search::{SearchStats, Solution};
+use crate::games::sliding::{Board, Move, MOVES};
+
+// Define an operator selection function: the heuristic together with the change of the
+// heuristic made by every legal move, found without generating the children
+pub trait OperatorSelection {
+    fn estimate(&self, board: &Board) -> u32;
+
+    fn deltas(&self, board: &Board) -> Vec<(Move, i32)>;
+}
+
+// Define the operator selection table of the Manhattan distance: the change of the distance
+// for every tile, cell the blank is on and move of the blank
+pub struct ManhattanOperators {
+    cells: usize,
+    distance: Vec<u32>,
+    delta: Vec<i8>,
+}
+
+impl ManhattanOperators {
+    pub fn new(goal: &Board) -> Self {
+        let (rows, cols) = (goal.rows(), goal.cols());
+        let cells = rows * cols;
+        let mut distance = vec![0; cells * cells];
+        for (home, &tile) in goal.tiles().iter().enumerate() {
+            if tile != 0 {
+                for cell in 0..cells {
+                    distance[tile as usize * cells + cell] = ((cell / cols).abs_diff(home / cols) + (cell % cols).abs_diff(home % cols)) as u32;
+                }
+            }
+        }
+        let mut delta = vec![0; cells * cells * MOVES.len()];
+        for blank in 0..cells {
+            let mut tiles = vec![1; cells];
+            tiles[0] = 0;
+            tiles.swap(0, blank);
+            let board = Board::from_tiles(rows, cols, tiles);
+            for (i, &m) in MOVES.iter().enumerate() {
+                if let Some(from) = board.target(m) {
+                    for tile in 1..cells {
+                        let change = distance[tile * cells + blank] as i32 - distance[tile * cells + from] as i32;
+                        delta[(tile * cells + blank) * MOVES.len() + i] = change as i8;
+                    }
+                }
+            }
+        }
+        Self { cells, distance, delta }
+    }
+}
+
+impl OperatorSelection for ManhattanOperators {
+    fn estimate(&self, board: &Board) -> u32 {
+        board.tiles().iter().enumerate().map(|(cell, &tile)| self.distance[tile as usize * self.cells + cell]).sum()
+    }
+
+    fn deltas(&self, board: &Board) -> Vec<(Move, i32)> {
+        let (row, col) = board.blank();
+        let blank = row * board.cols() + col;
+        MOVES
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &m)| board.tile_moved_by(m).map(|tile| (m, self.delta[(tile as usize * self.cells + blank) * MOVES.len() + i] as i32)))
+            .collect()
+    }
+}
+
+// A move only changes the lookup of the database holding the moved tile
+impl<D: Copy + Into<u32>> OperatorSelection for AdditivePatternDatabase<D> {
+    fn estimate(&self, board: &Board) -> u32 {
+        AdditivePatternDatabase::estimate(self, board)
+    }
+
+    fn deltas(&self, board: &Board) -> Vec<(Move, i32)> {
+        let mut cell_of = cells_of(board);
+        let (row, col) = board.blank();
+        let blank = row * board.cols() + col;
+        MOVES
+            .iter()
+            .filter_map(|&m| board.tile_moved_by(m).map(|tile| (m, self.move_delta(&mut cell_of, tile, blank))))
+            .collect()
+    }
+}
+
+struct Node {
+    board: Board,
+    h: u32,
+    parent: Option<(usize, Move)>,
+}
+
+// Define enhanced partial expansion A* (EPEA*) for unit costs and consistent heuristics.
+// Expanding a board only generates the children whose f-value equals the stored f-value of the
+// board, using the operator selection function to pick them; the board then goes back on the
+// open list with the next larger child f-value, so children that may never be needed are never
+// stored.
+pub fn epea_star_search(start: &Board, goal: &Board, operators: &dyn OperatorSelection) -> Option<Solution> {
+    let mut stats = SearchStats::default();
+    let mut nodes = vec![Node { board: start.clone(), h: operators.estimate(start), parent: None }];
+    let mut queue = BucketOpenList::new(TieBreaking::LargerG);
+    queue.push(nodes[0].h, 0, 0);
+    let mut best_g: HashMap<Board, u32> = HashMap::new();
+    best_g.insert(start.clone(), 0);
+    while let Some((stored_f, g, index)) = queue.pop() {
+        // Skip entries left behind by a cheaper path to the same board
+        if best_g[&nodes[index].board] < g {
+            continue;
+        }
+        if nodes[index].board == *goal {
+            let mut moves = vec![];
+            let mut index = index;
+            while let Some((parent, m)) = nodes[index].parent {
+                moves.push(m);
+                index = parent;
+            }
+            moves.reverse();
+            return Some(Solution { moves, stats });
+        }
+        stats.expanded += 1;
+        let h = nodes[index].h;
+        let wanted = (stored_f - (g + h)) as i32;
+        let mut next = None;
+        for (m, delta) in operators.deltas(&nodes[index].board) {
+            let change = 1 + delta;
+            if change > wanted {
+                next = Some(next.map_or(change, |next: i32| next.min(change)));
+                continue;
+            }
+            if change < wanted {
+                continue;
+            }
+            let board = nodes[index].board.moved(m).expect("move is legal");
+            if best_g.get(&board).is_some_and(|&old| old <= g + 1) {
+                continue;
+            }
+            stats.generated += 1;
+            best_g.insert(board.clone(), g + 1);
+            queue.push(stored_f, g + 1, nodes.len());
+            nodes.push(Node { board, h: (h as i32 + delta) as u32, parent: Some((index, m)) });
+        }
+        if let Some(change) = next {
+            queue.push(g + h + change as u32, g, index);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::heuristics::manhattan_distance;
+    use crate::games::pattern_database::PatternDatabase;
+    use crate::games::test_support::{optimal_length, played, seeded_boards};
+
+    fn assert_optimal(operators: &dyn OperatorSelection) {
+        let goal = Board::goal(3, 3);
+        for board in seeded_boards(30) {
+            let solution = epea_star_search(&board, &goal, operators).expect("3x3 boards are solved");
+            assert_eq!(solution.moves.len() as u32, optimal_length(&board));
+            assert_eq!(played(&board, &solution.moves), Some(goal.clone()));
+        }
+    }
+
+    // Every delta must match the estimate of the child it stands for
+    fn assert_deltas(operators: &dyn OperatorSelection) {
+        for board in seeded_boards(30) {
+            let deltas = operators.deltas(&board);
+            assert_eq!(deltas.len(), board.successors().len());
+            for (m, delta) in deltas {
+                let mut child = board.clone();
+                assert!(child.apply(m));
+                assert_eq!(operators.estimate(&child) as i32 - operators.estimate(&board) as i32, delta);
+            }
+        }
+    }
+
+    #[test]
+    fn manhattan_operators_are_optimal() {
+        let operators = ManhattanOperators::new(&Board::goal(3, 3));
+        for board in seeded_boards(30) {
+            assert_eq!(operators.estimate(&board), manhattan_distance(&board));
+        }
+        assert_deltas(&operators);
+        assert_optimal(&operators);
+    }
+
+    #[test]
+    fn pattern_database_operators_are_optimal() {
+        let goal = Board::goal(3, 3);
+        let operators = AdditivePatternDatabase::new(vec![PatternDatabase::build(&goal, &[1, 2, 3, 4]), PatternDatabase::build(&goal, &[5, 6, 7, 8])]);
+        assert_deltas(&operators);
+        assert_optimal(&operators);
+    }
+}
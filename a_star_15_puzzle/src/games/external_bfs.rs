@@ -0,0 +1,385 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::games::pattern_database::Layout;
+use crate::games::sliding::Board;
+
+// Define the state space to enumerate: the cells of the blank and of the tracked tiles, every
+// other tile being indistinguishable. Tracking every tile gives the full state space, tracking
+// a few gives the space a pattern database with the blank is built over.
+struct Space {
+    layout: Layout,
+    rows: usize,
+    cols: usize,
+    // The blank first, then the tracked tiles
+    tracked: Vec<u16>,
+}
+
+impl Space {
+    // Rank the successors of a state, moving the blank in every direction
+    fn successors(&self, rank: u64, positions: &mut [usize], out: &mut Vec<u64>) {
+        self.layout.unrank(rank as usize, positions);
+        let (rows, cols) = (self.rows, self.cols);
+        let blank = positions[0];
+        let (row, col) = (blank / cols, blank % cols);
+        let neighbours = [
+            (row > 0).then(|| blank - cols),
+            (row + 1 < rows).then(|| blank + cols),
+            (col > 0).then(|| blank - 1),
+            (col + 1 < cols).then(|| blank + 1),
+        ];
+        for neighbour in neighbours.into_iter().flatten() {
+            let moved = positions.iter().position(|&cell| cell == neighbour);
+            positions[0] = neighbour;
+            if let Some(i) = moved {
+                positions[i] = blank;
+            }
+            out.push(self.layout.rank(positions.iter().copied()) as u64);
+            positions[0] = blank;
+            if let Some(i) = moved {
+                positions[i] = neighbour;
+            }
+        }
+    }
+
+    // Turn a rank back into the tile on every cell, None for cells of untracked tiles
+    fn configuration(&self, rank: u64) -> Vec<Option<u16>> {
+        let mut positions = vec![0; self.tracked.len()];
+        self.layout.unrank(rank as usize, &mut positions);
+        let mut cells = vec![None; self.rows * self.cols];
+        for (&tile, &cell) in self.tracked.iter().zip(&positions) {
+            cells[cell] = Some(tile);
+        }
+        cells
+    }
+}
+
+// Define a reader over a file of sorted ranks, holding the next rank to be read
+struct SortedRun {
+    reader: BufReader<File>,
+    head: Option<u64>,
+}
+
+impl SortedRun {
+    fn open(path: &Path) -> io::Result<Self> {
+        let mut run = Self { reader: BufReader::with_capacity(1 << 20, File::open(path)?), head: None };
+        run.advance()?;
+        Ok(run)
+    }
+
+    fn advance(&mut self) -> io::Result<()> {
+        let mut bytes = [0; 8];
+        self.head = match self.reader.read_exact(&mut bytes) {
+            Ok(()) => Some(u64::from_le_bytes(bytes)),
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => None,
+            Err(error) => return Err(error),
+        };
+        Ok(())
+    }
+
+    // Skip ranks below `rank`, telling whether `rank` itself is in the run
+    fn contains(&mut self, rank: u64) -> io::Result<bool> {
+        while self.head.is_some_and(|head| head < rank) {
+            self.advance()?;
+        }
+        Ok(self.head == Some(rank))
+    }
+}
+
+fn write_ranks(path: &Path, ranks: &[u64]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for rank in ranks {
+        writer.write_all(&rank.to_le_bytes())?;
+    }
+    writer.flush()
+}
+
+fn read_ranks(path: &Path) -> io::Result<Vec<u64>> {
+    let mut run = SortedRun::open(path)?;
+    let mut ranks = vec![];
+    while let Some(rank) = run.head {
+        ranks.push(rank);
+        run.advance()?;
+    }
+    Ok(ranks)
+}
+
+// Define the result of an external breadth-first search: the number of states at every depth
+// and the states at the largest depth, with None on the cells of untracked tiles
+#[derive(Clone, Debug)]
+pub struct ExternalDistribution {
+    pub counts: Vec<u64>,
+    pub antipodes: Vec<Vec<Option<u16>>>,
+}
+
+impl ExternalDistribution {
+    pub fn states(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    pub fn diameter(&self) -> usize {
+        self.counts.len() - 1
+    }
+}
+
+// Define the search kept in `dir`: one file of sorted ranks per level and a checkpoint naming
+// the space, the counts so far and whether the search is complete
+struct ExternalSearch {
+    space: Space,
+    dir: PathBuf,
+    buffer: usize,
+}
+
+impl ExternalSearch {
+    fn level_path(&self, depth: usize) -> PathBuf {
+        self.dir.join(format!("level_{}.bin", depth))
+    }
+
+    fn checkpoint_path(&self) -> PathBuf {
+        self.dir.join("checkpoint.txt")
+    }
+
+    fn header(&self) -> String {
+        let tracked: Vec<String> = self.space.tracked.iter().map(|tile| tile.to_string()).collect();
+        format!("{} {}\n{}", self.space.rows, self.space.cols, tracked.join(" "))
+    }
+
+    // Read the counts and the completion flag of a previous run over the same space
+    fn load_checkpoint(&self) -> Option<(Vec<u64>, bool)> {
+        let text = fs::read_to_string(self.checkpoint_path()).ok()?;
+        let mut lines = text.lines();
+        let header = format!("{}\n{}", lines.next()?, lines.next()?);
+        if header != self.header() {
+            return None;
+        }
+        let counts = lines.next()?.split_whitespace().map(|count| count.parse().ok()).collect::<Option<Vec<u64>>>()?;
+        let complete = lines.next() == Some("complete");
+        Some((counts, complete))
+    }
+
+    // Write the checkpoint next to the old one and swap it in, so a crash leaves a valid one
+    fn save_checkpoint(&self, counts: &[u64], complete: bool) -> io::Result<()> {
+        let counts: Vec<String> = counts.iter().map(|count| count.to_string()).collect();
+        let state = if complete { "complete" } else { "running" };
+        let temporary = self.dir.join("checkpoint.tmp");
+        fs::write(&temporary, format!("{}\n{}\n{}\n", self.header(), counts.join(" "), state))?;
+        fs::rename(temporary, self.checkpoint_path())
+    }
+
+    // Expand the level at `depth` into sorted runs of at most `buffer` ranks, then merge the
+    // runs, dropping duplicates and the states of the two previous levels (delayed duplicate
+    // detection), into the file of the next level. Returns the size of the next level.
+    fn expand(&self, depth: usize) -> io::Result<u64> {
+        let mut positions = vec![0; self.space.tracked.len()];
+        let mut pending = Vec::with_capacity(self.buffer + 4);
+        let mut runs = vec![];
+        let mut current = SortedRun::open(&self.level_path(depth))?;
+        loop {
+            let done = current.head.is_none();
+            if let Some(rank) = current.head {
+                self.space.successors(rank, &mut positions, &mut pending);
+                current.advance()?;
+            }
+            if pending.len() >= self.buffer || (done && !pending.is_empty()) {
+                pending.sort_unstable();
+                pending.dedup();
+                let path = self.dir.join(format!("run_{}_{}.bin", depth + 1, runs.len()));
+                write_ranks(&path, &pending)?;
+                runs.push(path);
+                pending.clear();
+            }
+            if done {
+                break;
+            }
+        }
+
+        let mut inputs = runs.iter().map(|path| SortedRun::open(path)).collect::<io::Result<Vec<_>>>()?;
+        let mut heap: BinaryHeap<Reverse<(u64, usize)>> = inputs.iter().enumerate().filter_map(|(i, run)| run.head.map(|rank| Reverse((rank, i)))).collect();
+        let mut previous = vec![SortedRun::open(&self.level_path(depth))?];
+        if depth > 0 {
+            previous.push(SortedRun::open(&self.level_path(depth - 1))?);
+        }
+        let temporary = self.dir.join("level.tmp");
+        let mut writer = BufWriter::new(File::create(&temporary)?);
+        let mut last = None;
+        let mut count = 0;
+        while let Some(Reverse((rank, i))) = heap.pop() {
+            inputs[i].advance()?;
+            if let Some(next) = inputs[i].head {
+                heap.push(Reverse((next, i)));
+            }
+            if last == Some(rank) {
+                continue;
+            }
+            last = Some(rank);
+            let mut seen = false;
+            for level in &mut previous {
+                seen |= level.contains(rank)?;
+            }
+            if !seen {
+                writer.write_all(&rank.to_le_bytes())?;
+                count += 1;
+            }
+        }
+        writer.flush()?;
+        drop(writer);
+        fs::rename(temporary, self.level_path(depth + 1))?;
+        for path in runs {
+            fs::remove_file(path)?;
+        }
+        Ok(count)
+    }
+}
+
+// Check that every tracked tile is a distinct tile of the goal other than the blank, which is
+// always tracked
+pub fn validate_pattern(goal: &Board, pattern: &[u16]) -> io::Result<()> {
+    for (i, &tile) in pattern.iter().enumerate() {
+        let problem = if tile == 0 {
+            "the blank is always tracked"
+        } else if !goal.tiles().contains(&tile) {
+            "the tile is not on the board"
+        } else if pattern[..i].contains(&tile) {
+            "the tile is tracked twice"
+        } else {
+            continue;
+        };
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("cannot track tile {}: {}", tile, problem)));
+    }
+    Ok(())
+}
+
+// Define a disk-backed breadth-first search from `goal` over the space of the blank and the
+// `pattern` tiles (every tile when the pattern is empty, see `validate_pattern`), for boards of at most 64 cells whose
+// space has fewer than 2^64 states. Memory use is bounded by `buffer` ranks; levels live in a
+// directory of `dir` named after the space, and a checkpoint after every level lets an
+// interrupted search resume where it stopped. `progress` is called with every finished level.
+pub fn external_bfs(goal: &Board, pattern: &[u16], dir: &Path, buffer: usize, progress: &mut dyn FnMut(usize, u64)) -> io::Result<ExternalDistribution> {
+    validate_pattern(goal, pattern)?;
+    let mut tracked = vec![0];
+    if pattern.is_empty() {
+        tracked.extend(goal.tiles().iter().copied().filter(|&tile| tile != 0));
+    } else {
+        tracked.extend(pattern);
+    }
+    let layout = Layout::new(goal);
+    let names: Vec<String> = tracked.iter().map(|tile| tile.to_string()).collect();
+    let dir = dir.join(format!("bfs_{}x{}_{}", goal.rows(), goal.cols(), names.join("_")));
+    fs::create_dir_all(&dir)?;
+    let space = Space { layout, rows: goal.rows(), cols: goal.cols(), tracked };
+    let search = ExternalSearch { space, dir, buffer: buffer.max(1) };
+
+    let (mut counts, complete) = match search.load_checkpoint() {
+        Some(checkpoint) => checkpoint,
+        None => {
+            let home = search.space.tracked.iter().map(|&tile| goal.tiles().iter().position(|&t| t == tile).expect("tracked tile is on the board"));
+            write_ranks(&search.level_path(0), &[search.space.layout.rank(home) as u64])?;
+            search.save_checkpoint(&[1], false)?;
+            (vec![1], false)
+        }
+    };
+    for (depth, &count) in counts.iter().enumerate() {
+        progress(depth, count);
+    }
+    if !complete {
+        loop {
+            let depth = counts.len() - 1;
+            let count = search.expand(depth)?;
+            if count == 0 {
+                fs::remove_file(search.level_path(depth + 1))?;
+                search.save_checkpoint(&counts, true)?;
+                break;
+            }
+            counts.push(count);
+            search.save_checkpoint(&counts, false)?;
+            // Only the last two levels are needed to find the next one
+            if depth > 0 {
+                fs::remove_file(search.level_path(depth - 1))?;
+            }
+            progress(depth + 1, count);
+        }
+    }
+    let antipodes = read_ranks(&search.level_path(counts.len() - 1))?.into_iter().map(|rank| search.space.configuration(rank)).collect();
+    Ok(ExternalDistribution { counts, antipodes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::state_space::depth_distribution;
+    use crate::games::test_support::temporary_path;
+
+    // Copy a tree of directories and files
+    fn copy_files(from: &Path, to: &Path) {
+        fs::create_dir_all(to).unwrap();
+        for entry in fs::read_dir(from).unwrap() {
+            let path = entry.unwrap().path();
+            let target = to.join(path.file_name().unwrap());
+            if path.is_dir() {
+                copy_files(&path, &target);
+            } else {
+                fs::copy(&path, target).unwrap();
+            }
+        }
+    }
+
+    fn assert_matches_depth_distribution(rows: usize, cols: usize) {
+        let goal = Board::goal(rows, cols);
+        let expected = depth_distribution(&goal).unwrap();
+        let dir = temporary_path(&format!("external_bfs_{}x{}", rows, cols));
+        let snapshot = temporary_path(&format!("external_bfs_{}x{}_snapshot", rows, cols));
+        let mut levels = vec![];
+        let distribution = external_bfs(&goal, &[], &dir, 1000, &mut |depth, count| {
+            levels.push((depth, count));
+            // Keep the files as they stand halfway, as if the search had been stopped there
+            if depth == 10 {
+                copy_files(&dir, &snapshot);
+            }
+        })
+        .unwrap();
+        assert_eq!(distribution.counts, expected.counts);
+        assert_eq!(levels, distribution.counts.iter().copied().enumerate().collect::<Vec<_>>());
+        let hardest: Vec<Vec<Option<u16>>> = expected.hardest.iter().map(|board| board.tiles().iter().map(|&tile| Some(tile)).collect()).collect();
+        assert_eq!(distribution.antipodes, hardest);
+
+        // A second run reads the finished search back from the checkpoint
+        let mut levels = 0;
+        let resumed = external_bfs(&goal, &[], &dir, 1000, &mut |_, _| levels += 1).unwrap();
+        assert_eq!(resumed.counts, expected.counts);
+        assert_eq!(levels, expected.counts.len());
+        assert_eq!(resumed.antipodes, distribution.antipodes);
+
+        // Resuming from the halfway checkpoint finds the remaining levels
+        fs::remove_dir_all(&dir).unwrap();
+        copy_files(&snapshot, &dir);
+        let mut levels = 0;
+        let resumed = external_bfs(&goal, &[], &dir, 1000, &mut |_, _| levels += 1).unwrap();
+        assert_eq!(resumed.counts, expected.counts);
+        assert_eq!(levels, expected.counts.len());
+        assert_eq!(resumed.antipodes, distribution.antipodes);
+        fs::remove_dir_all(dir).unwrap();
+        fs::remove_dir_all(snapshot).unwrap();
+    }
+
+    #[test]
+    fn levels_match_the_depth_distribution() {
+        assert_matches_depth_distribution(2, 3);
+        assert_matches_depth_distribution(3, 3);
+    }
+
+    #[test]
+    fn patterns_must_name_distinct_tiles() {
+        let goal = Board::goal(3, 3);
+        assert!(validate_pattern(&goal, &[]).is_ok());
+        assert!(validate_pattern(&goal, &[1, 2, 8]).is_ok());
+        let dir = temporary_path("external_bfs_invalid");
+        for pattern in [&[0, 1][..], &[9], &[1, 2, 1]] {
+            let error = external_bfs(&goal, pattern, &dir, 1000, &mut |_, _| {}).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput, "{:?}", pattern);
+        }
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,135 @@
+use rand::prelude::*;
+use rand_pcg::Pcg64;
+
+use crate::games::heuristics::{linear_conflict_to, manhattan_distance};
+use crate::games::search;
+use crate::games::sliding::{self, Board};
+use crate::games::state_space::{self, UNREACHABLE};
+
+// Boards up to this many cells get an exact distance table (10! bytes), larger ones are solved
+pub const TABLE_CELLS: usize = 10;
+
+// Number of boards tried before giving up on a distance range
+const MAX_ATTEMPTS: usize = 1000;
+
+// Define a generator of boards with a known optimal solution length, reproducible from a seed.
+// Small boards are looked up in an exact distance table, larger ones are checked with IDA*
+// (Manhattan distance plus linear conflicts), which gets slow for very deep 4x4 boards and
+// beyond.
+pub struct Generator {
+    rows: usize,
+    cols: usize,
+    goal: Board,
+    rng: Pcg64,
+    table: Option<Vec<u8>>,
+}
+
+impl Generator {
+    pub fn new(rows: usize, cols: usize, seed: u64) -> Self {
+        let goal = Board::goal(rows, cols);
+        let table = if rows * cols <= TABLE_CELLS { state_space::distance_table(&goal) } else { None };
+        Self { rows, cols, goal, rng: Pcg64::seed_from_u64(seed), table }
+    }
+
+    // Optimal solution length of a board
+    pub fn distance(&self, board: &Board) -> u32 {
+        match &self.table {
+            Some(table) => table[state_space::rank(board.tiles())] as u32,
+            None => {
+                let heuristic = |board: &Board| manhattan_distance(board) + linear_conflict_to(board, &self.goal);
+                search::ida_star_search(board, &self.goal, &heuristic).expect("board is solvable").moves.len() as u32
+            }
+        }
+    }
+
+    // Draw a board whose optimal solution length lies in min..=max, uniformly among all such
+    // boards when a table is available. Without a table, random walks that never undo their
+    // previous move are lengthened or shortened until the distance lands in the range. Returns
+    // None when no board is found, e.g. when the range lies beyond the diameter.
+    pub fn with_distance(&mut self, min: u32, max: u32) -> Option<(Board, u32)> {
+        if let Some(table) = &self.table {
+            let matching = table.iter().filter(|&&distance| distance != UNREACHABLE && (min..=max).contains(&(distance as u32))).count();
+            if matching == 0 {
+                return None;
+            }
+            let pick = self.rng.gen_range(0..matching);
+            let (rank, &distance) = table
+                .iter()
+                .enumerate()
+                .filter(|&(_, &distance)| distance != UNREACHABLE && (min..=max).contains(&(distance as u32)))
+                .nth(pick)
+                .expect("pick is below the number of matching boards");
+            let tiles = state_space::unrank(rank, self.rows * self.cols);
+            return Some((Board::from_tiles(self.rows, self.cols, tiles), distance as u32));
+        }
+        // Walk lengths keep the parity of the distance, so aim at a length of the right parity
+        let mut length = max as usize;
+        for _ in 0..MAX_ATTEMPTS {
+            let board = sliding::walked_board(self.rows, self.cols, length, &mut self.rng);
+            let distance = self.distance(&board);
+            if (min..=max).contains(&distance) {
+                return Some((board, distance));
+            }
+            if distance < min {
+                length += (min - distance) as usize;
+                if min == max && length % 2 != min as usize % 2 {
+                    length += 1;
+                }
+            } else {
+                length = length.saturating_sub(2).max(max as usize);
+            }
+        }
+        None
+    }
+
+    // Draw `samples` uniformly random boards and keep the deepest one, which samples from the
+    // deep end of the distance distribution: the larger `samples`, the rarer the board
+    pub fn hard(&mut self, samples: usize) -> (Board, u32) {
+        (0..samples.max(1))
+            .map(|_| {
+                let board = sliding::shuffled_board(self.rows, self.cols, &mut self.rng);
+                let distance = self.distance(&board);
+                (board, distance)
+            })
+            .max_by_key(|&(_, distance)| distance)
+            .expect("at least one sample is drawn")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::test_support::optimal_length;
+
+    #[test]
+    fn table_boards_have_the_requested_distance() {
+        let mut generator = Generator::new(3, 3, 7);
+        for distance in [0, 1, 2, 15, 30, 31] {
+            let (board, found) = generator.with_distance(distance, distance).expect("3x3 boards reach every distance up to 31");
+            assert_eq!(found, distance);
+            assert_eq!(optimal_length(&board), distance);
+        }
+        let (board, found) = generator.with_distance(10, 12).unwrap();
+        assert!((10..=12).contains(&found));
+        assert_eq!(optimal_length(&board), found);
+        assert!(generator.with_distance(32, 40).is_none());
+    }
+
+    #[test]
+    fn walked_boards_have_the_requested_distance() {
+        let mut generator = Generator::new(4, 4, 7);
+        let goal = Board::goal(4, 4);
+        for distance in [1, 8, 15, 24] {
+            let (board, found) = generator.with_distance(distance, distance).expect("4x4 boards reach shallow distances");
+            assert_eq!(found, distance);
+            let solution = search::a_star_search(&board, &goal, &manhattan_distance).unwrap();
+            assert_eq!(solution.moves.len() as u32, distance);
+        }
+    }
+
+    #[test]
+    fn boards_repeat_with_the_seed() {
+        let draw = |seed| Generator::new(3, 4, seed).with_distance(20, 22);
+        assert_eq!(draw(3), draw(3));
+    }
+}
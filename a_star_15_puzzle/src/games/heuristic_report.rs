@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use std::hint::black_box;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use crate::games::search;
+use crate::games::sliding::Board;
+
+// Minimum time spent calling a heuristic when measuring the time per call
+const TIMING_BUDGET: Duration = Duration::from_millis(50);
+
+// Define the errors h* - h of a heuristic over the boards at one optimal distance
+#[derive(Clone, Copy, Default, Debug)]
+pub struct DepthErrors {
+    pub boards: usize,
+    pub mean_h: f64,
+    pub mean_error: f64,
+    pub min_error: i64,
+    pub max_error: i64,
+}
+
+// Define the quality of one heuristic over a set of boards with known optimal distances
+#[derive(Clone, Debug)]
+pub struct HeuristicReport {
+    pub name: String,
+    // Mean of h / h* over the boards away from the goal
+    pub mean_ratio: f64,
+    // Pearson correlation between h and h*
+    pub correlation: f64,
+    // Boards on which h exceeds h*, any means the heuristic is not admissible
+    pub overestimates: usize,
+    pub errors_by_depth: BTreeMap<u32, DepthErrors>,
+    pub nanos_per_call: f64,
+    // Boards expanded by IDA* over all boards, when the searches were run
+    pub ida_expanded: Option<u64>,
+}
+
+fn correlation(pairs: &[(f64, f64)]) -> f64 {
+    let n = pairs.len() as f64;
+    let (mean_x, mean_y) = (pairs.iter().map(|p| p.0).sum::<f64>() / n, pairs.iter().map(|p| p.1).sum::<f64>() / n);
+    let covariance: f64 = pairs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let spread_x: f64 = pairs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>().sqrt();
+    let spread_y: f64 = pairs.iter().map(|(_, y)| (y - mean_y).powi(2)).sum::<f64>().sqrt();
+    if spread_x == 0.0 || spread_y == 0.0 {
+        return 0.0;
+    }
+    covariance / (spread_x * spread_y)
+}
+
+// Measure one heuristic on `instances`, pairs of a board and its optimal distance to `goal`.
+// With `run_ida` every board is also solved with IDA* to count the boards expanded.
+pub fn analyse(name: &str, heuristic: &dyn Fn(&Board) -> u32, instances: &[(Board, u32)], goal: &Board, run_ida: bool) -> HeuristicReport {
+    let estimates: Vec<u32> = instances.iter().map(|(board, _)| heuristic(board)).collect();
+    let pairs: Vec<(f64, f64)> = estimates.iter().zip(instances).map(|(&h, &(_, distance))| (h as f64, distance as f64)).collect();
+    let ratios: Vec<f64> = pairs.iter().filter(|&&(_, distance)| distance > 0.0).map(|(h, distance)| h / distance).collect();
+    let mean_ratio = if ratios.is_empty() { 1.0 } else { ratios.iter().sum::<f64>() / ratios.len() as f64 };
+
+    let mut errors_by_depth: BTreeMap<u32, DepthErrors> = BTreeMap::new();
+    for (&h, &(_, distance)) in estimates.iter().zip(instances) {
+        let error = distance as i64 - h as i64;
+        let errors = errors_by_depth.entry(distance).or_insert(DepthErrors { min_error: error, max_error: error, ..DepthErrors::default() });
+        errors.boards += 1;
+        errors.mean_h += h as f64;
+        errors.mean_error += error as f64;
+        errors.min_error = errors.min_error.min(error);
+        errors.max_error = errors.max_error.max(error);
+    }
+    for errors in errors_by_depth.values_mut() {
+        errors.mean_h /= errors.boards as f64;
+        errors.mean_error /= errors.boards as f64;
+    }
+
+    let mut calls = 0;
+    let now = Instant::now();
+    while now.elapsed() < TIMING_BUDGET && !instances.is_empty() {
+        for (board, _) in instances {
+            black_box(heuristic(black_box(board)));
+        }
+        calls += instances.len();
+    }
+    let nanos_per_call = now.elapsed().as_nanos() as f64 / calls.max(1) as f64;
+
+    let ida_expanded = run_ida.then(|| {
+        instances
+            .iter()
+            .filter_map(|(board, _)| search::ida_star_search(board, goal, heuristic))
+            .map(|solution| solution.stats.expanded as u64)
+            .sum()
+    });
+    HeuristicReport {
+        name: name.to_string(),
+        mean_ratio,
+        correlation: correlation(&pairs),
+        overestimates: estimates.iter().zip(instances).filter(|&(&h, &(_, distance))| h > distance).count(),
+        errors_by_depth,
+        nanos_per_call,
+        ida_expanded,
+    }
+}
+
+// Write one line per heuristic with the summary figures
+pub fn write_summary_csv(writer: &mut dyn Write, reports: &[HeuristicReport]) -> io::Result<()> {
+    writeln!(writer, "heuristic,mean_ratio,correlation,overestimates,nanos_per_call,ida_expanded")?;
+    for report in reports {
+        let expanded = report.ida_expanded.map_or(String::new(), |expanded| expanded.to_string());
+        writeln!(writer, "{},{:.4},{:.4},{},{:.1},{}", report.name, report.mean_ratio, report.correlation, report.overestimates, report.nanos_per_call, expanded)?;
+    }
+    Ok(())
+}
+
+// Write one line per heuristic and optimal distance with the error distribution
+pub fn write_errors_csv(writer: &mut dyn Write, reports: &[HeuristicReport]) -> io::Result<()> {
+    writeln!(writer, "heuristic,distance,boards,mean_h,mean_error,min_error,max_error")?;
+    for report in reports {
+        for (distance, errors) in &report.errors_by_depth {
+            writeln!(writer, "{},{},{},{:.3},{:.3},{},{}", report.name, distance, errors.boards, errors.mean_h, errors.mean_error, errors.min_error, errors.max_error)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::heuristics::manhattan_distance;
+    use crate::games::test_support::{optimal_length, seeded_boards};
+
+    fn instances() -> Vec<(Board, u32)> {
+        seeded_boards(30).into_iter().map(|board| (board.clone(), optimal_length(&board))).collect()
+    }
+
+    #[test]
+    fn exact_heuristic_has_no_error() {
+        let instances = instances();
+        let report = analyse("exact", &optimal_length, &instances, &Board::goal(3, 3), true);
+        assert_eq!(report.mean_ratio, 1.0);
+        assert!((report.correlation - 1.0).abs() < 1e-9);
+        assert_eq!(report.overestimates, 0);
+        assert_eq!(report.errors_by_depth.values().map(|errors| errors.boards).sum::<usize>(), instances.len());
+        assert!(report.errors_by_depth.values().all(|errors| errors.min_error == 0 && errors.max_error == 0));
+        // With the exact distance IDA* expands only the boards along one optimal path
+        assert_eq!(report.ida_expanded, Some(instances.iter().map(|&(_, distance)| distance as u64).sum()));
+    }
+
+    #[test]
+    fn errors_are_counted_per_depth() {
+        let instances = instances();
+        let report = analyse("manhattan", &manhattan_distance, &instances, &Board::goal(3, 3), false);
+        assert_eq!(report.overestimates, 0);
+        assert!(report.mean_ratio > 0.0 && report.mean_ratio <= 1.0);
+        assert_eq!(report.ida_expanded, None);
+        for (&distance, errors) in &report.errors_by_depth {
+            let boards: Vec<u32> = instances.iter().filter(|&&(_, d)| d == distance).map(|(board, _)| manhattan_distance(board)).collect();
+            assert_eq!(errors.boards, boards.len());
+            assert_eq!(errors.max_error, distance as i64 - *boards.iter().min().unwrap() as i64);
+            assert_eq!(errors.min_error, distance as i64 - *boards.iter().max().unwrap() as i64);
+        }
+        let overestimating = analyse("doubled", &|board| 2 * manhattan_distance(board), &instances, &Board::goal(3, 3), false);
+        assert!(overestimating.overestimates > 0);
+    }
+
+    #[test]
+    fn csv_has_a_line_per_report_and_depth() {
+        let instances = instances();
+        let reports = [analyse("exact", &optimal_length, &instances, &Board::goal(3, 3), false)];
+        let mut summary = vec![];
+        write_summary_csv(&mut summary, &reports).unwrap();
+        let summary = String::from_utf8(summary).unwrap();
+        assert_eq!(summary.lines().count(), 2);
+        assert!(summary.lines().nth(1).unwrap().starts_with("exact,1.0000,1.0000,0,"));
+        let mut errors = vec![];
+        write_errors_csv(&mut errors, &reports).unwrap();
+        assert_eq!(String::from_utf8(errors).unwrap().lines().count(), 1 + reports[0].errors_by_depth.len());
+    }
+}
@@ -0,0 +1,451 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::games::cost::CostModel;
+use crate::games::sliding::Board;
+use crate::games::walking_distance;
+
+// Define the goal position of every tile, indexed by tile
+fn goal_positions(goal: &Board) -> Vec<usize> {
+    let mut positions = vec![0; goal.tiles().len()];
+    for (i, &tile) in goal.tiles().iter().enumerate() {
+        positions[tile as usize] = i;
+    }
+    positions
+}
+
+// Define the linear conflict penalty of one row or column, given the goal positions along that
+// line of the tiles that belong in it, in the order they currently appear
+pub fn linear_conflict_penalty(line: &[usize]) -> u32 {
+    // The longest increasing subsequence can stay, everything else is in conflict
+    let mut tails: Vec<usize> = vec![];
+    for &position in line {
+        match tails.binary_search(&position) {
+            Ok(_) => {}
+            Err(i) if i == tails.len() => tails.push(position),
+            Err(i) => tails[i] = position,
+        }
+    }
+    2 * (line.len() - tails.len()) as u32
+}
+
+// Define the Manhattan distance heuristic function for boards of any size
+pub fn manhattan_distance(board: &Board) -> u32 {
+    let cols = board.cols();
+    let mut distance = 0;
+    for (i, &tile) in board.tiles().iter().enumerate() {
+        if tile != 0 {
+            let goal = tile as usize - 1;
+            distance += (i / cols).abs_diff(goal / cols) + (i % cols).abs_diff(goal % cols);
+        }
+    }
+    distance as u32
+}
+
+// Define the Manhattan distance heuristic function towards any goal board
+pub fn manhattan_distance_to(board: &Board, goal: &Board) -> u32 {
+    let cols = board.cols();
+    let goal_position = goal_positions(goal);
+    let mut distance = 0;
+    for (i, &tile) in board.tiles().iter().enumerate() {
+        if tile != 0 {
+            let goal = goal_position[tile as usize];
+            distance += (i / cols).abs_diff(goal / cols) + (i % cols).abs_diff(goal % cols);
+        }
+    }
+    distance as u32
+}
+
+// Define the Manhattan distance weighted by the cost of moving every tile, it stays admissible
+// because every tile must make at least its Manhattan distance in moves of its own cost
+pub fn weighted_manhattan_distance_to(board: &Board, goal: &Board, costs: &CostModel) -> u32 {
+    let cols = board.cols();
+    let goal_position = goal_positions(goal);
+    let mut distance = 0;
+    for (i, &tile) in board.tiles().iter().enumerate() {
+        if tile != 0 {
+            let goal = goal_position[tile as usize];
+            let moves = (i / cols).abs_diff(goal / cols) + (i % cols).abs_diff(goal % cols);
+            distance += moves as u32 * costs.tile_cost(tile);
+        }
+    }
+    distance
+}
+
+// Define the linear conflict penalty of a whole board towards any goal board,
+// it is admissible when added to the Manhattan distance
+pub fn linear_conflict_to(board: &Board, goal: &Board) -> u32 {
+    let (rows, cols) = (board.rows(), board.cols());
+    let goal_position = goal_positions(goal);
+    let mut count = 0;
+    for row in 0..rows {
+        let line: Vec<usize> = (0..cols)
+            .map(|col| board.tiles()[row * cols + col] as usize)
+            .filter(|&tile| tile != 0 && goal_position[tile] / cols == row)
+            .map(|tile| goal_position[tile] % cols)
+            .collect();
+        count += linear_conflict_penalty(&line);
+    }
+    for col in 0..cols {
+        let line: Vec<usize> = (0..rows)
+            .map(|row| board.tiles()[row * cols + col] as usize)
+            .filter(|&tile| tile != 0 && goal_position[tile] % cols == col)
+            .map(|tile| goal_position[tile] / cols)
+            .collect();
+        count += linear_conflict_penalty(&line);
+    }
+    count
+}
+
+// Define the walking distance heuristic for any rows x cols board and goal. The horizontal part
+// counts the vertical moves needed when tiles only have to reach their goal row, the vertical
+// part the same for columns.
+pub struct WalkingDistance {
+    rows: usize,
+    cols: usize,
+    goal_row: Vec<usize>,
+    goal_col: Vec<usize>,
+    horizontal: Arc<HashMap<Vec<u8>, u8>>,
+    vertical: Arc<HashMap<Vec<u8>, u8>>,
+}
+
+impl WalkingDistance {
+    pub fn new(goal: &Board) -> Self {
+        let (rows, cols) = (goal.rows(), goal.cols());
+        let goal_position = goal_positions(goal);
+        let (blank_row, blank_col) = goal.blank();
+        Self {
+            rows,
+            cols,
+            goal_row: goal_position.iter().map(|&i| i / cols).collect(),
+            goal_col: goal_position.iter().map(|&i| i % cols).collect(),
+            horizontal: walking_distance::general_lookup_table(rows, cols, blank_row),
+            vertical: walking_distance::general_lookup_table(cols, rows, blank_col),
+        }
+    }
+
+    pub fn estimate(&self, board: &Board) -> u32 {
+        let mut horizontal = vec![0; self.rows * self.rows];
+        let mut vertical = vec![0; self.cols * self.cols];
+        for (i, &tile) in board.tiles().iter().enumerate() {
+            if tile != 0 {
+                let (row, col) = (i / self.cols, i % self.cols);
+                horizontal[row * self.rows + self.goal_row[tile as usize]] += 1;
+                vertical[col * self.cols + self.goal_col[tile as usize]] += 1;
+            }
+        }
+        self.horizontal[&horizontal] as u32 + self.vertical[&vertical] as u32
+    }
+}
+
+// Define a heuristic object that can be chosen and combined at runtime. Besides the estimate it
+// declares whether it never overestimates, and which tiles' moves it counts: a heuristic that
+// counts only the moves of its own tiles can be summed with heuristics over other tiles.
+pub trait Heuristic {
+    fn name(&self) -> String;
+
+    fn estimate(&self, board: &Board) -> u32;
+
+    fn is_admissible(&self) -> bool;
+
+    // The tiles whose moves the estimate counts, None when it is not additive with others
+    fn additive_tiles(&self) -> Option<Vec<u16>>;
+}
+
+fn all_tiles(goal: &Board) -> Vec<u16> {
+    goal.tiles().iter().copied().filter(|&tile| tile != 0).collect()
+}
+
+// Define the Manhattan distance towards a goal as a heuristic object
+pub struct Manhattan {
+    goal: Board,
+}
+
+impl Manhattan {
+    pub fn new(goal: &Board) -> Self {
+        Self { goal: goal.clone() }
+    }
+}
+
+impl Heuristic for Manhattan {
+    fn name(&self) -> String {
+        "Manhattan".to_string()
+    }
+
+    fn estimate(&self, board: &Board) -> u32 {
+        manhattan_distance_to(board, &self.goal)
+    }
+
+    fn is_admissible(&self) -> bool {
+        true
+    }
+
+    fn additive_tiles(&self) -> Option<Vec<u16>> {
+        Some(all_tiles(&self.goal))
+    }
+}
+
+// Define the Manhattan distance plus the linear conflict penalty as a heuristic object
+pub struct LinearConflict {
+    goal: Board,
+}
+
+impl LinearConflict {
+    pub fn new(goal: &Board) -> Self {
+        Self { goal: goal.clone() }
+    }
+}
+
+impl Heuristic for LinearConflict {
+    fn name(&self) -> String {
+        "Manhattan + linear conflict".to_string()
+    }
+
+    fn estimate(&self, board: &Board) -> u32 {
+        manhattan_distance_to(board, &self.goal) + linear_conflict_to(board, &self.goal)
+    }
+
+    fn is_admissible(&self) -> bool {
+        true
+    }
+
+    fn additive_tiles(&self) -> Option<Vec<u16>> {
+        Some(all_tiles(&self.goal))
+    }
+}
+
+impl Heuristic for WalkingDistance {
+    fn name(&self) -> String {
+        "Walking distance".to_string()
+    }
+
+    fn estimate(&self, board: &Board) -> u32 {
+        WalkingDistance::estimate(self, board)
+    }
+
+    fn is_admissible(&self) -> bool {
+        true
+    }
+
+    fn additive_tiles(&self) -> Option<Vec<u16>> {
+        Some((1..self.goal_row.len() as u16).collect())
+    }
+}
+
+// Define the largest estimate of several heuristics, admissible when all of them are
+pub struct MaxOf {
+    parts: Vec<Box<dyn Heuristic>>,
+}
+
+impl MaxOf {
+    pub fn new(parts: Vec<Box<dyn Heuristic>>) -> Self {
+        Self { parts }
+    }
+}
+
+impl Heuristic for MaxOf {
+    fn name(&self) -> String {
+        let names: Vec<String> = self.parts.iter().map(|part| part.name()).collect();
+        format!("max({})", names.join(", "))
+    }
+
+    fn estimate(&self, board: &Board) -> u32 {
+        self.parts.iter().map(|part| part.estimate(board)).max().unwrap_or(0)
+    }
+
+    fn is_admissible(&self) -> bool {
+        self.parts.iter().all(|part| part.is_admissible())
+    }
+
+    // Every part counts only moves of tiles in the union, so the largest does as well
+    fn additive_tiles(&self) -> Option<Vec<u16>> {
+        let mut tiles = vec![];
+        for part in &self.parts {
+            tiles.extend(part.additive_tiles()?);
+        }
+        tiles.sort_unstable();
+        tiles.dedup();
+        Some(tiles)
+    }
+}
+
+// Define the sum of several heuristics. It stays admissible only when every part is admissible
+// and counts the moves of tiles no other part counts.
+pub struct SumOf {
+    parts: Vec<Box<dyn Heuristic>>,
+}
+
+impl SumOf {
+    pub fn new(parts: Vec<Box<dyn Heuristic>>) -> Self {
+        Self { parts }
+    }
+}
+
+impl Heuristic for SumOf {
+    fn name(&self) -> String {
+        let names: Vec<String> = self.parts.iter().map(|part| part.name()).collect();
+        format!("sum({})", names.join(", "))
+    }
+
+    fn estimate(&self, board: &Board) -> u32 {
+        self.parts.iter().map(|part| part.estimate(board)).sum()
+    }
+
+    fn is_admissible(&self) -> bool {
+        self.parts.iter().all(|part| part.is_admissible()) && self.additive_tiles().is_some()
+    }
+
+    // The union of the tiles of the parts, None when two parts count the same tile
+    fn additive_tiles(&self) -> Option<Vec<u16>> {
+        let mut tiles = vec![];
+        for part in &self.parts {
+            tiles.extend(part.additive_tiles()?);
+        }
+        let count = tiles.len();
+        tiles.sort_unstable();
+        tiles.dedup();
+        (tiles.len() == count).then_some(tiles)
+    }
+}
+
+// Define a heuristic scaled by a weight, as used by weighted A*. A weight above one trades
+// optimality for speed, so the result is then neither admissible nor additive.
+pub struct Scaled {
+    weight: f64,
+    inner: Box<dyn Heuristic>,
+}
+
+impl Scaled {
+    pub fn new(weight: f64, inner: Box<dyn Heuristic>) -> Self {
+        Self { weight, inner }
+    }
+}
+
+impl Heuristic for Scaled {
+    fn name(&self) -> String {
+        format!("{} * {}", self.weight, self.inner.name())
+    }
+
+    fn estimate(&self, board: &Board) -> u32 {
+        (self.inner.estimate(board) as f64 * self.weight) as u32
+    }
+
+    fn is_admissible(&self) -> bool {
+        self.weight <= 1.0 && self.inner.is_admissible()
+    }
+
+    fn additive_tiles(&self) -> Option<Vec<u16>> {
+        if self.weight <= 1.0 {
+            self.inner.additive_tiles()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::search;
+    use crate::games::sliding;
+    use crate::games::test_support::{optimal_length, seeded_boards};
+
+    // Define the Manhattan distance of a few tiles, additive with the distance of the others
+    struct PartialManhattan {
+        goal: Board,
+        tiles: Vec<u16>,
+    }
+
+    impl Heuristic for PartialManhattan {
+        fn name(&self) -> String {
+            format!("Manhattan {:?}", self.tiles)
+        }
+
+        fn estimate(&self, board: &Board) -> u32 {
+            let cols = board.cols();
+            let cell = |board: &Board, tile| board.tiles().iter().position(|&t| t == tile).unwrap();
+            self.tiles
+                .iter()
+                .map(|&tile| {
+                    let (from, to) = (cell(board, tile), cell(&self.goal, tile));
+                    ((from / cols).abs_diff(to / cols) + (from % cols).abs_diff(to % cols)) as u32
+                })
+                .sum()
+        }
+
+        fn is_admissible(&self) -> bool {
+            true
+        }
+
+        fn additive_tiles(&self) -> Option<Vec<u16>> {
+            Some(self.tiles.clone())
+        }
+    }
+
+    fn partial(tiles: &[u16]) -> Box<dyn Heuristic> {
+        Box::new(PartialManhattan { goal: Board::goal(3, 3), tiles: tiles.to_vec() })
+    }
+
+    // Compare both heuristics with the optimal distance of boards scrambled from the goal
+    fn assert_admissible(rows: usize, cols: usize) {
+        let goal = Board::goal(rows, cols);
+        let walking_distance = WalkingDistance::new(&goal);
+        for seed in 0..40 {
+            let start = sliding::seeded_n_random_moves_from_goal(rows, cols, 40, seed);
+            let optimal = search::a_star_search(&start, &goal, &manhattan_distance).unwrap().moves.len() as u32;
+            assert!(walking_distance.estimate(&start) <= optimal, "walking distance on {}x{} seed {}", rows, cols, seed);
+            assert!(manhattan_distance(&start) + linear_conflict_to(&start, &goal) <= optimal, "linear conflicts on {}x{} seed {}", rows, cols, seed);
+        }
+    }
+
+    #[test]
+    fn heuristics_are_admissible_on_rectangular_boards() {
+        assert_admissible(2, 5);
+        assert_admissible(5, 2);
+    }
+
+    #[test]
+    fn maximum_takes_the_largest_part() {
+        let goal = Board::goal(3, 3);
+        let max = MaxOf::new(vec![Box::new(Manhattan::new(&goal)), Box::new(LinearConflict::new(&goal)), partial(&[1, 2])]);
+        assert_eq!(max.name(), "max(Manhattan, Manhattan + linear conflict, Manhattan [1, 2])");
+        assert!(max.is_admissible());
+        assert_eq!(max.additive_tiles(), Some((1..9).collect()));
+        for board in seeded_boards(50) {
+            assert_eq!(max.estimate(&board), manhattan_distance(&board) + linear_conflict_to(&board, &goal));
+            assert!(max.estimate(&board) <= optimal_length(&board));
+        }
+    }
+
+    #[test]
+    fn sum_is_admissible_only_over_disjoint_tiles() {
+        let goal = Board::goal(3, 3);
+        let disjoint = SumOf::new(vec![partial(&[1, 2, 3, 4]), partial(&[5, 6, 7, 8])]);
+        assert!(disjoint.is_admissible());
+        assert_eq!(disjoint.additive_tiles(), Some((1..9).collect()));
+        for board in seeded_boards(50) {
+            assert_eq!(disjoint.estimate(&board), manhattan_distance(&board));
+        }
+        let overlapping = SumOf::new(vec![partial(&[1, 2, 3]), Box::new(Manhattan::new(&goal))]);
+        assert!(!overlapping.is_admissible());
+        assert_eq!(overlapping.additive_tiles(), None);
+        let opaque = MaxOf::new(vec![partial(&[1]), Box::new(Scaled::new(2.0, partial(&[2])))]);
+        assert_eq!(opaque.additive_tiles(), None);
+        assert!(!SumOf::new(vec![partial(&[3]), Box::new(opaque)]).is_admissible());
+    }
+
+    #[test]
+    fn scaling_above_one_is_inadmissible() {
+        let goal = Board::goal(3, 3);
+        let heavier = Scaled::new(1.5, Box::new(Manhattan::new(&goal)));
+        let lighter = Scaled::new(0.5, Box::new(Manhattan::new(&goal)));
+        assert!(!heavier.is_admissible());
+        assert_eq!(heavier.additive_tiles(), None);
+        assert!(lighter.is_admissible());
+        assert_eq!(lighter.additive_tiles(), Some((1..9).collect()));
+        for board in seeded_boards(50) {
+            assert_eq!(heavier.estimate(&board), manhattan_distance(&board) * 3 / 2);
+            assert_eq!(lighter.estimate(&board), manhattan_distance(&board) / 2);
+        }
+    }
+}
@@ -0,0 +1,367 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use rand::prelude::*;
+use rand_pcg::Pcg64;
+
+use crate::games::heuristics::{linear_conflict_to, manhattan_distance_to, Heuristic};
+use crate::games::sliding::Board;
+
+// Distances are divided by this before training, to keep the outputs near one
+const SCALE: f64 = 10.0;
+
+// Define the input of the network for a board: how far every tile is from its goal row and
+// column, the cell of the blank, and the Manhattan distance and linear conflicts as a whole
+pub fn features(board: &Board, goal: &Board) -> Vec<f64> {
+    let (rows, cols) = (board.rows(), board.cols());
+    let cells = board.tiles().len();
+    let mut goal_position = vec![0; cells];
+    for (cell, &tile) in goal.tiles().iter().enumerate() {
+        goal_position[tile as usize] = cell;
+    }
+    let mut features = vec![0.0; 2 * (cells - 1) + cells + 2];
+    for (cell, &tile) in board.tiles().iter().enumerate() {
+        if tile == 0 {
+            features[2 * (cells - 1) + cell] = 1.0;
+            continue;
+        }
+        let home = goal_position[tile as usize];
+        let slot = tile as usize - 1;
+        features[2 * slot] = (cell / cols).abs_diff(home / cols) as f64 / rows as f64;
+        features[2 * slot + 1] = (cell % cols).abs_diff(home % cols) as f64 / cols as f64;
+    }
+    features[2 * (cells - 1) + cells] = manhattan_distance_to(board, goal) as f64 / SCALE;
+    features[2 * (cells - 1) + cells + 1] = linear_conflict_to(board, goal) as f64 / SCALE;
+    features
+}
+
+// Define one fully connected layer, weights stored row by row (one row per output)
+#[derive(Clone)]
+struct Layer {
+    inputs: usize,
+    outputs: usize,
+    weights: Vec<f64>,
+    biases: Vec<f64>,
+}
+
+impl Layer {
+    fn new(inputs: usize, outputs: usize, rng: &mut Pcg64) -> Self {
+        // He initialisation, suited to ReLU
+        let spread = (6.0 / inputs as f64).sqrt();
+        let weights = (0..inputs * outputs).map(|_| rng.gen_range(-spread..spread)).collect();
+        Self { inputs, outputs, weights, biases: vec![0.0; outputs] }
+    }
+
+    fn forward(&self, input: &[f64], relu: bool) -> Vec<f64> {
+        (0..self.outputs)
+            .map(|o| {
+                let row = &self.weights[o * self.inputs..(o + 1) * self.inputs];
+                let sum = self.biases[o] + row.iter().zip(input).map(|(w, x)| w * x).sum::<f64>();
+                if relu {
+                    sum.max(0.0)
+                } else {
+                    sum
+                }
+            })
+            .collect()
+    }
+}
+
+// Define the Adam moments of every parameter of a layer
+struct Moments {
+    weights: (Vec<f64>, Vec<f64>),
+    biases: (Vec<f64>, Vec<f64>),
+}
+
+// Define a small feed-forward regressor: ReLU hidden layers and one linear output
+#[derive(Clone)]
+pub struct Network {
+    layers: Vec<Layer>,
+}
+
+impl Network {
+    pub fn new(sizes: &[usize], seed: u64) -> Self {
+        let mut rng = Pcg64::seed_from_u64(seed);
+        Self { layers: sizes.windows(2).map(|pair| Layer::new(pair[0], pair[1], &mut rng)).collect() }
+    }
+
+    pub fn predict(&self, input: &[f64]) -> f64 {
+        let last = self.layers.len() - 1;
+        let mut activation = input.to_vec();
+        for (i, layer) in self.layers.iter().enumerate() {
+            activation = layer.forward(&activation, i != last);
+        }
+        activation[0]
+    }
+
+    // Run one epoch of mini-batch Adam on the squared error, returning the mean squared error
+    fn train_epoch(&mut self, data: &[(Vec<f64>, f64)], batch: usize, rate: f64, moments: &mut [Moments], step: &mut i32, rng: &mut Pcg64) -> f64 {
+        let (beta1, beta2, epsilon) = (0.9, 0.999, 1e-8);
+        let mut order: Vec<usize> = (0..data.len()).collect();
+        order.shuffle(rng);
+        let mut total = 0.0;
+        for chunk in order.chunks(batch.max(1)) {
+            let mut weight_gradients: Vec<Vec<f64>> = self.layers.iter().map(|layer| vec![0.0; layer.weights.len()]).collect();
+            let mut bias_gradients: Vec<Vec<f64>> = self.layers.iter().map(|layer| vec![0.0; layer.biases.len()]).collect();
+            for &i in chunk {
+                let (input, target) = &data[i];
+                let last = self.layers.len() - 1;
+                let mut activations = vec![input.clone()];
+                for (l, layer) in self.layers.iter().enumerate() {
+                    let next = layer.forward(&activations[l], l != last);
+                    activations.push(next);
+                }
+                let error = activations[last + 1][0] - target;
+                total += error * error;
+                // Back-propagate the gradient of the squared error
+                let mut delta = vec![2.0 * error];
+                for l in (0..self.layers.len()).rev() {
+                    let layer = &self.layers[l];
+                    let input = &activations[l];
+                    let mut previous = vec![0.0; layer.inputs];
+                    for o in 0..layer.outputs {
+                        bias_gradients[l][o] += delta[o];
+                        for j in 0..layer.inputs {
+                            weight_gradients[l][o * layer.inputs + j] += delta[o] * input[j];
+                            previous[j] += delta[o] * layer.weights[o * layer.inputs + j];
+                        }
+                    }
+                    // The inputs of every layer but the first went through a ReLU
+                    if l > 0 {
+                        for (gradient, &x) in previous.iter_mut().zip(input) {
+                            if x <= 0.0 {
+                                *gradient = 0.0;
+                            }
+                        }
+                    }
+                    delta = previous;
+                }
+            }
+            *step += 1;
+            let (correction1, correction2) = (1.0 - f64::powi(beta1, *step), 1.0 - f64::powi(beta2, *step));
+            let scale = 1.0 / chunk.len() as f64;
+            for ((layer, moment), (weight_gradient, bias_gradient)) in self.layers.iter_mut().zip(moments.iter_mut()).zip(weight_gradients.iter().zip(&bias_gradients)) {
+                let updates = [(&mut layer.weights, &mut moment.weights, weight_gradient), (&mut layer.biases, &mut moment.biases, bias_gradient)];
+                for (parameters, (first, second), gradients) in updates {
+                    for i in 0..parameters.len() {
+                        let gradient = gradients[i] * scale;
+                        first[i] = beta1 * first[i] + (1.0 - beta1) * gradient;
+                        second[i] = beta2 * second[i] + (1.0 - beta2) * gradient * gradient;
+                        parameters[i] -= rate * (first[i] / correction1) / ((second[i] / correction2).sqrt() + epsilon);
+                    }
+                }
+            }
+        }
+        total / data.len().max(1) as f64
+    }
+}
+
+// Define the training settings of a learned heuristic
+#[derive(Clone, Copy, Debug)]
+pub struct Training {
+    pub hidden: usize,
+    pub epochs: usize,
+    pub batch: usize,
+    pub rate: f64,
+    // Share of the boards kept aside to measure the error
+    pub validation: f64,
+    // Share of the held-out boards that must not be overestimated after the correction
+    pub quantile: f64,
+    pub seed: u64,
+}
+
+impl Default for Training {
+    fn default() -> Self {
+        Self { hidden: 64, epochs: 50, batch: 32, rate: 1e-3, validation: 0.2, quantile: 0.95, seed: 0 }
+    }
+}
+
+// Define what training reports about the held-out boards
+#[derive(Clone, Copy, Debug)]
+pub struct Evaluation {
+    pub train_error: f64,
+    pub mean_absolute_error: f64,
+    // Amount subtracted from every prediction
+    pub offset: f64,
+    // Share of the held-out boards still overestimated after the correction
+    pub overestimated: f64,
+}
+
+// Define a heuristic learned from boards with known optimal distances. It predicts the distance
+// with a small network and subtracts an offset, a quantile of the overestimation on held-out
+// boards, which makes overestimates rare but does not rule them out.
+pub struct LearnedHeuristic {
+    goal: Board,
+    network: Network,
+    offset: f64,
+}
+
+impl LearnedHeuristic {
+    // Train on (board, optimal distance) pairs, every board sharing the size of `goal`
+    pub fn train(goal: &Board, instances: &[(Board, u32)], training: &Training, progress: &mut dyn FnMut(usize, f64)) -> (Self, Evaluation) {
+        let mut rng = Pcg64::seed_from_u64(training.seed);
+        let mut data: Vec<(Vec<f64>, f64)> = instances.iter().map(|(board, distance)| (features(board, goal), *distance as f64 / SCALE)).collect();
+        data.shuffle(&mut rng);
+        let held_out = ((data.len() as f64 * training.validation) as usize).min(data.len().saturating_sub(1));
+        let (validation, train) = data.split_at(held_out);
+        let inputs = train.first().map_or(0, |(input, _)| input.len());
+        let mut network = Network::new(&[inputs, training.hidden, training.hidden, 1], training.seed);
+        let mut moments: Vec<Moments> = network
+            .layers
+            .iter()
+            .map(|layer| Moments {
+                weights: (vec![0.0; layer.weights.len()], vec![0.0; layer.weights.len()]),
+                biases: (vec![0.0; layer.biases.len()], vec![0.0; layer.biases.len()]),
+            })
+            .collect();
+        let mut step = 0;
+        let mut train_error = 0.0;
+        for epoch in 0..training.epochs {
+            train_error = network.train_epoch(train, training.batch, training.rate, &mut moments, &mut step, &mut rng) * SCALE * SCALE;
+            progress(epoch, train_error);
+        }
+
+        // Choose the offset from the overestimation of the held-out boards
+        let checked = if validation.is_empty() { train } else { validation };
+        let mut errors: Vec<f64> = checked.iter().map(|(input, target)| (network.predict(input) - target) * SCALE).collect();
+        errors.sort_by(f64::total_cmp);
+        let mean_absolute_error = errors.iter().map(|error| error.abs()).sum::<f64>() / errors.len().max(1) as f64;
+        let index = ((errors.len() as f64 * training.quantile).ceil() as usize).clamp(1, errors.len().max(1)) - 1;
+        let offset = errors.get(index).copied().unwrap_or(0.0).max(0.0);
+        let overestimated = errors.iter().filter(|&&error| error - offset > 0.0).count() as f64 / errors.len().max(1) as f64;
+        let learned = Self { goal: goal.clone(), network, offset };
+        (learned, Evaluation { train_error, mean_absolute_error, offset, overestimated })
+    }
+
+    // Save as text: the board size and goal, the offset, the layer sizes and then the biases and
+    // weights of every layer
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut text = format!("{} {}\n", self.goal.rows(), self.goal.cols());
+        let goal: Vec<String> = self.goal.tiles().iter().map(|tile| tile.to_string()).collect();
+        text += &format!("{}\n{}\n", goal.join(" "), self.offset);
+        let mut sizes = vec![self.network.layers[0].inputs.to_string()];
+        sizes.extend(self.network.layers.iter().map(|layer| layer.outputs.to_string()));
+        text += &format!("{}\n", sizes.join(" "));
+        for layer in &self.network.layers {
+            let biases: Vec<String> = layer.biases.iter().map(|bias| bias.to_string()).collect();
+            let weights: Vec<String> = layer.weights.iter().map(|weight| weight.to_string()).collect();
+            text += &format!("{}\n{}\n", biases.join(" "), weights.join(" "));
+        }
+        fs::write(path, text)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed model file");
+        let text = fs::read_to_string(path)?;
+        let mut lines = text.lines();
+        let numbers = |lines: &mut std::str::Lines| -> io::Result<Vec<f64>> {
+            lines.next().ok_or_else(invalid)?.split_whitespace().map(|number| number.parse().map_err(|_| invalid())).collect()
+        };
+        let size = numbers(&mut lines)?;
+        let tiles: Vec<u16> = numbers(&mut lines)?.into_iter().map(|tile| tile as u16).collect();
+        let (rows, cols) = (*size.first().ok_or_else(invalid)? as usize, *size.get(1).ok_or_else(invalid)? as usize);
+        let mut sorted = tiles.clone();
+        sorted.sort_unstable();
+        if rows * cols < 2 || sorted.len() != rows * cols || sorted.iter().enumerate().any(|(i, &tile)| tile as usize != i) {
+            return Err(invalid());
+        }
+        let offset = *numbers(&mut lines)?.first().ok_or_else(invalid)?;
+        let sizes: Vec<usize> = numbers(&mut lines)?.into_iter().map(|size| size as usize).collect();
+        // The network must take the features of the stored goal's board size and give one output
+        let cells = rows * cols;
+        if sizes.first() != Some(&(2 * (cells - 1) + cells + 2)) || sizes.last() != Some(&1) {
+            return Err(invalid());
+        }
+        let mut layers = vec![];
+        for pair in sizes.windows(2) {
+            let (biases, weights) = (numbers(&mut lines)?, numbers(&mut lines)?);
+            if biases.len() != pair[1] || weights.len() != pair[0] * pair[1] {
+                return Err(invalid());
+            }
+            layers.push(Layer { inputs: pair[0], outputs: pair[1], weights, biases });
+        }
+        if layers.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self { goal: Board::from_tiles(rows, cols, tiles), network: Network { layers }, offset })
+    }
+
+    pub fn goal(&self) -> &Board {
+        &self.goal
+    }
+}
+
+impl Heuristic for LearnedHeuristic {
+    fn name(&self) -> String {
+        format!("Learned (offset {:.2})", self.offset)
+    }
+
+    fn estimate(&self, board: &Board) -> u32 {
+        let prediction = self.network.predict(&features(board, &self.goal)) * SCALE - self.offset;
+        prediction.round().max(0.0) as u32
+    }
+
+    fn is_admissible(&self) -> bool {
+        false
+    }
+
+    fn additive_tiles(&self) -> Option<Vec<u16>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::test_support::{optimal_length, seeded_boards, temporary_path};
+
+    fn trained() -> (LearnedHeuristic, Evaluation) {
+        let instances: Vec<(Board, u32)> = seeded_boards(300).into_iter().map(|board| (board.clone(), optimal_length(&board))).collect();
+        let training = Training { hidden: 8, epochs: 5, ..Training::default() };
+        let mut epochs = 0;
+        let trained = LearnedHeuristic::train(&Board::goal(3, 3), &instances, &training, &mut |_, _| epochs += 1);
+        assert_eq!(epochs, training.epochs);
+        trained
+    }
+
+    #[test]
+    fn features_describe_every_tile() {
+        let goal = Board::goal(3, 4);
+        let features = features(&goal, &goal);
+        assert_eq!(features.len(), 2 * 11 + 12 + 2);
+        // Only the blank on its goal cell is set
+        assert_eq!(features.iter().filter(|&&feature| feature != 0.0).count(), 1);
+        assert_eq!(features[2 * 11 + 11], 1.0);
+    }
+
+    #[test]
+    fn offset_covers_the_held_out_quantile() {
+        let (learned, evaluation) = trained();
+        assert!(evaluation.offset >= 0.0);
+        assert!(evaluation.overestimated <= 1.0 - Training::default().quantile + 1e-9);
+        assert!(!learned.is_admissible());
+    }
+
+    #[test]
+    fn saved_models_load_back() {
+        let (learned, _) = trained();
+        let path = temporary_path("learned_model.txt");
+        learned.save(&path).unwrap();
+        let loaded = LearnedHeuristic::load(&path).unwrap();
+        assert_eq!(loaded.goal(), learned.goal());
+        assert_eq!(loaded.name(), learned.name());
+        for board in seeded_boards(50) {
+            assert_eq!(loaded.estimate(&board), learned.estimate(&board));
+        }
+
+        // Truncated files and networks for another board size are rejected
+        let text = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        fs::write(&path, lines[..lines.len() - 1].join("\n")).unwrap();
+        assert_eq!(LearnedHeuristic::load(&path).err().map(|error| error.kind()), Some(io::ErrorKind::InvalidData));
+        fs::write(&path, text.replacen("3 3\n1 2 3 4 5 6 7 8 0", "2 2\n1 2 3 0", 1)).unwrap();
+        assert_eq!(LearnedHeuristic::load(&path).err().map(|error| error.kind()), Some(io::ErrorKind::InvalidData));
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::games::sliding::{Board, Move};
+
+// Define which lookups of a table-based heuristic make up the estimate
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Selection {
+    Regular,
+    // The larger of the regular and the dual lookup
+    MaxRegularDual,
+    // The largest of every available lookup
+    MaxAll,
+    // One available lookup picked pseudo-randomly per board, which makes the heuristic
+    // inconsistent, so it is best used with BPMX
+    Random,
+}
+
+// Define the extra lookups of a table-based heuristic. A board with the blank on its goal cell
+// has the same distance to the goal as its dual (the inverse permutation), and on square boards
+// with the goal blank on the main diagonal every board has the same distance as its reflection
+// about that diagonal, so looking those up in the same table gives further admissible estimates
+// from the same memory.
+pub struct SymmetricLookups<'a> {
+    goal: Board,
+    goal_position: Vec<usize>,
+    table: &'a dyn Fn(&Board) -> u32,
+    reflectable: bool,
+}
+
+impl<'a> SymmetricLookups<'a> {
+    pub fn new(goal: &Board, table: &'a dyn Fn(&Board) -> u32) -> Self {
+        let mut goal_position = vec![0; goal.tiles().len()];
+        for (cell, &tile) in goal.tiles().iter().enumerate() {
+            goal_position[tile as usize] = cell;
+        }
+        let (row, col) = goal.blank();
+        Self {
+            goal: goal.clone(),
+            goal_position,
+            table,
+            reflectable: goal.rows() == goal.cols() && row == col,
+        }
+    }
+
+    // Swap the roles of tiles and cells: the dual holds, on the goal cell of every tile, the goal
+    // tile of the cell where that tile stands now. Duality only holds with the blank on its goal
+    // cell, so the blank is first walked home; the length of that walk is returned as well,
+    // since the board may be that much closer to the goal than the dual.
+    pub fn dual(&self, board: &Board) -> (Board, u32) {
+        let mut board = board.clone();
+        let (row, col) = board.blank();
+        let (goal_row, goal_col) = self.goal.blank();
+        let vertical = if row < goal_row { Move::Down } else { Move::Up };
+        let horizontal = if col < goal_col { Move::Right } else { Move::Left };
+        for _ in 0..row.abs_diff(goal_row) {
+            board.apply(vertical);
+        }
+        for _ in 0..col.abs_diff(goal_col) {
+            board.apply(horizontal);
+        }
+        let mut position = vec![0; board.tiles().len()];
+        for (cell, &tile) in board.tiles().iter().enumerate() {
+            position[tile as usize] = cell;
+        }
+        let goal = self.goal.tiles();
+        let tiles = goal.iter().map(|&tile| goal[position[tile as usize]]).collect();
+        let walk = (row.abs_diff(goal_row) + col.abs_diff(goal_col)) as u32;
+        (Board::from_tiles(board.rows(), board.cols(), tiles), walk)
+    }
+
+    fn dual_estimate(&self, board: &Board) -> u32 {
+        let (dual, walk) = self.dual(board);
+        (self.table)(&dual).saturating_sub(walk)
+    }
+
+    // Mirror the board about the main diagonal and rename every tile after its mirrored goal cell
+    pub fn reflected(&self, board: &Board) -> Option<Board> {
+        if !self.reflectable {
+            return None;
+        }
+        let size = board.cols();
+        let goal = self.goal.tiles();
+        let mut tiles = vec![0; board.tiles().len()];
+        for (cell, &tile) in board.tiles().iter().enumerate() {
+            let home = self.goal_position[tile as usize];
+            tiles[(cell % size) * size + cell / size] = goal[(home % size) * size + home / size];
+        }
+        Some(Board::from_tiles(size, size, tiles))
+    }
+
+    pub fn estimate(&self, board: &Board, selection: Selection) -> u32 {
+        let table = self.table;
+        match selection {
+            Selection::Regular => table(board),
+            Selection::MaxRegularDual => table(board).max(self.dual_estimate(board)),
+            Selection::MaxAll => {
+                let reflected = self.reflected(board).map_or(0, |reflected| table(&reflected));
+                table(board).max(self.dual_estimate(board)).max(reflected)
+            }
+            Selection::Random => {
+                let mut hasher = DefaultHasher::new();
+                board.hash(&mut hasher);
+                let lookups = if self.reflectable { 3 } else { 2 };
+                match hasher.finish() % lookups {
+                    0 => table(board),
+                    1 => self.dual_estimate(board),
+                    _ => table(&self.reflected(board).expect("board can be reflected")),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::heuristics::manhattan_distance;
+    use crate::games::test_support::{optimal_length, seeded_boards};
+
+    #[test]
+    fn symmetric_boards_keep_the_distance() {
+        let goal = Board::goal(3, 3);
+        let lookups = SymmetricLookups::new(&goal, &optimal_length);
+        for board in seeded_boards(200) {
+            let reflected = lookups.reflected(&board).expect("3x3 boards can be reflected");
+            assert_eq!(optimal_length(&reflected), optimal_length(&board));
+            let (dual, walk) = lookups.dual(&board);
+            if walk == 0 {
+                assert_eq!(optimal_length(&dual), optimal_length(&board));
+                assert_eq!(lookups.dual(&dual).0, board);
+            }
+        }
+    }
+
+    #[test]
+    fn every_selection_is_admissible() {
+        let goal = Board::goal(3, 3);
+        let lookups = SymmetricLookups::new(&goal, &manhattan_distance);
+        for board in seeded_boards(200) {
+            let optimal = optimal_length(&board);
+            let regular = lookups.estimate(&board, Selection::Regular);
+            assert!(lookups.estimate(&board, Selection::MaxRegularDual) >= regular);
+            assert!(lookups.estimate(&board, Selection::MaxAll) >= lookups.estimate(&board, Selection::MaxRegularDual));
+            for selection in [Selection::Regular, Selection::MaxRegularDual, Selection::MaxAll, Selection::Random] {
+                assert!(lookups.estimate(&board, selection) <= optimal, "{:?} overestimates", selection);
+            }
+        }
+    }
+}
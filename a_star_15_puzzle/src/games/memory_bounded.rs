@@ -0,0 +1,349 @@
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::rc::Rc;
+
+use crate::games::open_list::{BucketOpenList, TieBreaking};
+use crate::games::search::{SearchStats, Solution};
+use crate::games::sliding::{Board, Move};
+
+// Define what an SMA* node knows about one of its successors
+#[derive(Clone, Copy)]
+enum Slot {
+    // Not generated yet
+    Pending,
+    InMemory(usize),
+    // Dropped to free memory, keeping its backed-up f-value
+    Forgotten(u32),
+}
+
+struct SmaNode {
+    board: Board,
+    g: u32,
+    f: u32,
+    depth: usize,
+    parent: Option<(usize, Move)>,
+    slots: Vec<(Move, Slot)>,
+}
+
+// Define the node store of SMA*, which never holds more than `limit` nodes
+struct SmaTree {
+    nodes: Vec<Option<SmaNode>>,
+    free: Vec<usize>,
+    // Nodes that can still generate a successor, ordered by f and then by depth
+    open: BTreeSet<(u32, Reverse<usize>, usize)>,
+}
+
+impl SmaTree {
+    fn node(&self, index: usize) -> &SmaNode {
+        self.nodes[index].as_ref().expect("node is in memory")
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut SmaNode {
+        self.nodes[index].as_mut().expect("node is in memory")
+    }
+
+    fn live(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    fn key(&self, index: usize) -> (u32, Reverse<usize>, usize) {
+        let node = self.node(index);
+        (node.f, Reverse(node.depth), index)
+    }
+
+    fn insert(&mut self, node: SmaNode) -> usize {
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = Some(node);
+                index
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+        self.open.insert(self.key(index));
+        index
+    }
+
+    fn set_f(&mut self, index: usize, f: u32) {
+        let was_open = self.open.remove(&self.key(index));
+        self.node_mut(index).f = f;
+        if was_open {
+            self.open.insert(self.key(index));
+        }
+    }
+
+    // Once every successor of a node has been generated, its f-value is the smallest f-value
+    // among them; pass the change on to the ancestors
+    fn back_up(&mut self, mut index: usize) {
+        loop {
+            let node = self.node(index);
+            let mut f = u32::MAX;
+            for &(_, slot) in &node.slots {
+                f = f.min(match slot {
+                    Slot::Pending => return,
+                    Slot::InMemory(child) => self.node(child).f,
+                    Slot::Forgotten(f) => f,
+                });
+            }
+            if f == node.f {
+                return;
+            }
+            let parent = node.parent;
+            self.set_f(index, f);
+            match parent {
+                Some((parent, _)) => index = parent,
+                None => return,
+            }
+        }
+    }
+
+    // Drop the shallowest leaf with the largest f-value, other than `keep`, and remember its
+    // f-value in its parent. Returns false when no leaf can be dropped.
+    fn forget_worst_leaf(&mut self, keep: usize) -> bool {
+        let worst = self.open.iter().rev().map(|&(_, _, index)| index).find(|&index| {
+            let node = self.node(index);
+            index != keep && node.parent.is_some() && node.slots.iter().all(|&(_, slot)| !matches!(slot, Slot::InMemory(_)))
+        });
+        let Some(index) = worst else {
+            return false;
+        };
+        self.open.remove(&self.key(index));
+        let node = self.nodes[index].take().expect("node is in memory");
+        self.free.push(index);
+        let (parent, m) = node.parent.expect("leaf is not the root");
+        for slot in &mut self.node_mut(parent).slots {
+            if slot.0 == m {
+                slot.1 = Slot::Forgotten(node.f);
+            }
+        }
+        self.open.insert(self.key(parent));
+        true
+    }
+
+    fn trace_moves(&self, mut index: usize) -> Vec<Move> {
+        let mut moves = vec![];
+        while let Some((parent, m)) = self.node(index).parent {
+            moves.push(m);
+            index = parent;
+        }
+        moves.reverse();
+        moves
+    }
+}
+
+fn slots(board: &Board, parent: Option<(usize, Move)>) -> Vec<(Move, Slot)> {
+    let back = parent.map(|(_, m)| m.opposite());
+    board
+        .successors()
+        .into_iter()
+        .filter(|&(m, _)| Some(m) != back)
+        .map(|(m, _)| (m, Slot::Pending))
+        .collect()
+}
+
+// Define simplified memory-bounded A* (SMA*): it grows the search tree one successor at a time
+// like A*, and when `limit` nodes are in memory it forgets the shallowest leaf with the largest
+// f-value, backing its f-value up into the parent so the subtree is only regenerated once
+// everything else looks worse. The solution is optimal whenever the optimal path fits in the
+// limit. Moves undoing the previous move are never generated.
+pub fn sma_star_search(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32, limit: usize) -> Option<Solution> {
+    let mut stats = SearchStats::default();
+    let limit = limit.max(2);
+    // A path with more nodes than the limit can never be held in memory
+    let max_depth = limit - 1;
+    let mut tree = SmaTree { nodes: vec![], free: vec![], open: BTreeSet::new() };
+    tree.insert(SmaNode { board: start.clone(), g: 0, f: heuristic(start), depth: 0, parent: None, slots: slots(start, None) });
+    while let Some(&(f, _, index)) = tree.open.first() {
+        if f == u32::MAX {
+            return None;
+        }
+        if tree.node(index).board == *goal {
+            return Some(Solution { moves: tree.trace_moves(index), stats });
+        }
+        stats.expanded += 1;
+        // Generate the next new successor, or else regenerate the most promising forgotten one
+        let node = tree.node(index);
+        let slot = node.slots.iter().position(|&(_, slot)| matches!(slot, Slot::Pending)).or_else(|| {
+            (0..node.slots.len())
+                .filter(|&i| matches!(node.slots[i].1, Slot::Forgotten(_)))
+                .min_by_key(|&i| match node.slots[i].1 {
+                    Slot::Forgotten(f) => f,
+                    _ => u32::MAX,
+                })
+        });
+        let Some(slot) = slot else {
+            tree.open.remove(&tree.key(index));
+            continue;
+        };
+        let (m, previous) = node.slots[slot];
+        let board = node.board.moved(m).expect("slot holds a legal move");
+        let (g, depth) = (node.g + 1, node.depth + 1);
+        let parent = Some((index, m));
+        let slots = slots(&board, parent);
+        let f = if board != *goal && (depth == max_depth || slots.is_empty()) {
+            u32::MAX
+        } else {
+            let forgotten = if let Slot::Forgotten(f) = previous { f } else { 0 };
+            (g + heuristic(&board)).max(node.f).max(forgotten)
+        };
+        stats.generated += 1;
+        if tree.live() >= limit && !tree.forget_worst_leaf(index) {
+            tree.node_mut(index).slots[slot].1 = Slot::Forgotten(f);
+        } else {
+            let child = tree.insert(SmaNode { board, g, f, depth, parent, slots });
+            tree.node_mut(index).slots[slot].1 = Slot::InMemory(child);
+        }
+        tree.back_up(index);
+        if tree.node(index).slots.iter().all(|&(_, slot)| matches!(slot, Slot::InMemory(_))) {
+            tree.open.remove(&tree.key(index));
+        }
+    }
+    None
+}
+
+fn bit(m: Move) -> u8 {
+    1 << m as u8
+}
+
+// Define one step of a path kept for the frontier: the move into a board and the step into its
+// parent. Steps are shared between the boards reached through them and freed with the last one,
+// so only the paths to boards still on the frontier stay in memory; `live` counts them.
+struct Step {
+    m: Move,
+    parent: Option<Rc<Step>>,
+    live: Rc<Cell<usize>>,
+}
+
+impl Step {
+    fn new(m: Move, parent: Option<Rc<Step>>, live: &Rc<Cell<usize>>) -> Rc<Self> {
+        live.set(live.get() + 1);
+        Rc::new(Self { m, parent, live: Rc::clone(live) })
+    }
+}
+
+impl Drop for Step {
+    fn drop(&mut self) {
+        self.live.set(self.live.get() - 1);
+        // Free long chains one step at a time rather than by recursion
+        let mut parent = self.parent.take();
+        while let Some(step) = parent {
+            match Rc::try_unwrap(step) {
+                Ok(mut step) => parent = step.parent.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+fn trace_steps(mut step: Option<&Rc<Step>>) -> Vec<Move> {
+    let mut moves = vec![];
+    while let Some(current) = step {
+        moves.push(current.m);
+        step = current.parent.as_ref();
+    }
+    moves.reverse();
+    moves
+}
+
+// Define a frontier node: the moves already used in or out of the board, so closed neighbours
+// are never generated again, and the last step of the path that reached it
+struct FrontierNode {
+    g: u32,
+    used: u8,
+    step: Option<Rc<Step>>,
+}
+
+// Define frontier A*, which keeps no closed list: every board on the frontier remembers which
+// of its neighbours were expanded already, so expanded boards can be dropped without being
+// generated again. Holds for consistent heuristics; with inconsistent ones an expanded board may
+// come back and be expanded again. Gives up once the frontier, the open list (stale entries
+// included) and the path steps still in use together hold more than `limit` entries.
+pub fn frontier_a_star_search(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32, limit: usize) -> Option<Solution> {
+    let mut stats = SearchStats::default();
+    let live = Rc::new(Cell::new(0));
+    let mut frontier: HashMap<Board, FrontierNode> = HashMap::new();
+    frontier.insert(start.clone(), FrontierNode { g: 0, used: 0, step: None });
+    let mut queue = BucketOpenList::new(TieBreaking::LargerG);
+    queue.push(heuristic(start), 0, start.clone());
+    while let Some((_, g, board)) = queue.pop() {
+        // Skip entries left behind by a cheaper path or by an expansion
+        if frontier.get(&board).is_none_or(|node| node.g != g) {
+            continue;
+        }
+        let node = frontier.remove(&board).expect("board is on the frontier");
+        if board == *goal {
+            return Some(Solution { moves: trace_steps(node.step.as_ref()), stats });
+        }
+        stats.expanded += 1;
+        for (m, successor) in board.successors() {
+            if node.used & bit(m) != 0 {
+                continue;
+            }
+            let back = bit(m.opposite());
+            match frontier.get_mut(&successor) {
+                Some(existing) => {
+                    existing.used |= back;
+                    if existing.g <= g + 1 {
+                        continue;
+                    }
+                    existing.g = g + 1;
+                    existing.step = Some(Step::new(m, node.step.clone(), &live));
+                }
+                None => {
+                    let step = Some(Step::new(m, node.step.clone(), &live));
+                    frontier.insert(successor.clone(), FrontierNode { g: g + 1, used: back, step });
+                }
+            }
+            stats.generated += 1;
+            queue.push(g + 1 + heuristic(&successor), g + 1, successor);
+        }
+        if frontier.len() + queue.entries() + live.get() > limit {
+            return None;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::heuristics::manhattan_distance;
+    use crate::games::sliding;
+    use crate::games::test_support::{optimal_length, played, seeded_boards};
+
+    #[test]
+    fn sma_star_is_optimal_within_its_limit() {
+        let goal = Board::goal(3, 3);
+        for board in seeded_boards(20) {
+            let solution = sma_star_search(&board, &goal, &manhattan_distance, 100).expect("the optimal path fits in 100 nodes");
+            assert_eq!(solution.moves.len() as u32, optimal_length(&board));
+            assert_eq!(played(&board, &solution.moves), Some(goal.clone()));
+        }
+    }
+
+    #[test]
+    fn sma_star_gives_up_when_the_path_does_not_fit() {
+        let goal = Board::goal(3, 3);
+        for seed in 0..5 {
+            let board = sliding::seeded_n_random_moves_from_goal(3, 3, 12, seed);
+            let optimal = optimal_length(&board) as usize;
+            // A path of n moves holds n + 1 boards
+            assert!(sma_star_search(&board, &goal, &manhattan_distance, optimal).is_none());
+            assert!(sma_star_search(&board, &goal, &manhattan_distance, optimal + 1).is_some_and(|solution| solution.moves.len() == optimal));
+        }
+    }
+
+    #[test]
+    fn frontier_a_star_is_optimal() {
+        let goal = Board::goal(3, 3);
+        for board in seeded_boards(20) {
+            let solution = frontier_a_star_search(&board, &goal, &manhattan_distance, usize::MAX).expect("3x3 boards are solved");
+            assert_eq!(solution.moves.len() as u32, optimal_length(&board));
+            assert_eq!(played(&board, &solution.moves), Some(goal.clone()));
+        }
+        assert!(frontier_a_star_search(&seeded_boards(1)[0], &goal, &manhattan_distance, 10).is_none());
+    }
+}
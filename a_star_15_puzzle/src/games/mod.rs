@@ -1,4 +1,31 @@
 pub mod puzzle8;
 pub mod puzzle15;
 pub mod walking_distance;
-pub mod puzzle15_WD;
\ No newline at end of file
+pub mod puzzle15_WD;
+pub mod sliding;
+pub mod heuristics;
+pub mod real_time;
+pub mod search;
+pub mod constructive;
+pub mod shorten;
+pub mod optimal_solutions;
+pub mod state_space;
+pub mod pattern_database;
+pub mod puzzle24;
+pub mod cost;
+pub mod lookups;
+pub mod open_list;
+pub mod memory_bounded;
+pub mod epea;
+pub mod external_bfs;
+pub mod generator;
+pub mod heuristic_report;
+pub mod learned;
+pub mod render;
+pub mod play;
+pub mod search_tree;
+pub mod observers;
+pub mod checkpoint;
+pub mod batch;
+#[cfg(test)]
+mod test_support;
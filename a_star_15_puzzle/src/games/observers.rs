@@ -0,0 +1,333 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use dialoguer::console::Term;
+
+use crate::games::search::{IdaState, SearchObserver, SearchStats};
+use crate::games::sliding::{moves_to_string, Board, Move};
+
+// Time between two redraws of the progress bar
+const REDRAW: Duration = Duration::from_millis(100);
+const BAR_WIDTH: usize = 30;
+
+// Define an observer passing every event on to several observers, in order
+pub struct Broadcast<'a>(pub Vec<&'a mut dyn SearchObserver>);
+
+impl SearchObserver for Broadcast<'_> {
+    fn on_expand(&mut self, board: &Board, g: u32, h: u32) {
+        self.0.iter_mut().for_each(|observer| observer.on_expand(board, g, h));
+    }
+
+    fn on_generate(&mut self, board: &Board, g: u32, h: u32) {
+        self.0.iter_mut().for_each(|observer| observer.on_generate(board, g, h));
+    }
+
+    fn on_duplicate(&mut self, board: &Board, g: u32) {
+        self.0.iter_mut().for_each(|observer| observer.on_duplicate(board, g));
+    }
+
+    fn on_iteration(&mut self, bound: u32, stats: &SearchStats) {
+        self.0.iter_mut().for_each(|observer| observer.on_iteration(bound, stats));
+    }
+
+    fn on_progress(&mut self, bound: u32, stats: &SearchStats) {
+        self.0.iter_mut().for_each(|observer| observer.on_progress(bound, stats));
+    }
+
+    fn on_solution(&mut self, moves: &[Move], stats: &SearchStats) {
+        self.0.iter_mut().for_each(|observer| observer.on_solution(moves, stats));
+    }
+
+    fn on_snapshot(&mut self, state: &IdaState) {
+        self.0.iter_mut().for_each(|observer| observer.on_snapshot(state));
+    }
+
+    fn should_stop(&mut self) -> bool {
+        self.0.iter_mut().any(|observer| observer.should_stop())
+    }
+}
+
+// Define a one-line progress bar on a terminal showing the f-value reached (the largest f
+// expanded by A*, the bound of IDA*), the boards expanded and the expansion rate. With a
+// `target`, e.g. a known solution length, the bar fills from the first f-value to the target.
+pub struct ProgressBar {
+    term: Term,
+    target: Option<u32>,
+    first_f: Option<u32>,
+    f: u32,
+    expanded: u64,
+    started: Instant,
+    drawn: Instant,
+}
+
+impl ProgressBar {
+    pub fn new(term: Term, target: Option<u32>) -> Self {
+        let now = Instant::now();
+        Self { term, target, first_f: None, f: 0, expanded: 0, started: now, drawn: now }
+    }
+
+    fn reach(&mut self, f: u32) {
+        self.first_f.get_or_insert(f);
+        self.f = self.f.max(f);
+    }
+
+    fn line(&self) -> String {
+        let rate = self.expanded as f64 / self.started.elapsed().as_secs_f64().max(1e-9);
+        let counters = format!("f {}, {} expanded, {:.0}/s", self.f, self.expanded, rate);
+        match (self.target, self.first_f) {
+            (Some(target), Some(first)) => {
+                let done = if target > first { (self.f.saturating_sub(first) as f64 / (target - first) as f64).min(1.0) } else { 1.0 };
+                let filled = (done * BAR_WIDTH as f64).round() as usize;
+                format!("[{}{}] {}/{}", "#".repeat(filled), ".".repeat(BAR_WIDTH - filled), counters, target)
+            }
+            _ => counters,
+        }
+    }
+
+    fn draw(&mut self) {
+        self.drawn = Instant::now();
+        if self.term.is_term() {
+            // The bar is best effort, a failed redraw is not worth stopping the search for
+            let _ = self.term.clear_line().and_then(|_| self.term.write_str(&self.line()));
+        }
+    }
+}
+
+impl SearchObserver for ProgressBar {
+    fn on_expand(&mut self, _board: &Board, g: u32, h: u32) {
+        self.expanded += 1;
+        self.reach(g + h);
+        if self.expanded.is_multiple_of(1024) && self.drawn.elapsed() >= REDRAW {
+            self.draw();
+        }
+    }
+
+    fn on_iteration(&mut self, bound: u32, _stats: &SearchStats) {
+        self.reach(bound);
+        self.draw();
+    }
+
+    fn on_solution(&mut self, moves: &[Move], _stats: &SearchStats) {
+        self.reach(moves.len() as u32);
+        self.draw();
+        let _ = self.term.write_line("");
+    }
+}
+
+// Define counts of the search events by g-value, together with the bound and the boards
+// expanded before every IDA* iteration and the solution length once found
+#[derive(Clone, Debug, Default)]
+pub struct EventHistogram {
+    pub expanded: BTreeMap<u32, u64>,
+    pub generated: BTreeMap<u32, u64>,
+    pub duplicates: BTreeMap<u32, u64>,
+    pub iterations: Vec<(u32, usize)>,
+    pub solution: Option<usize>,
+}
+
+impl EventHistogram {
+    // Total number of events of every kind: expanded, generated and duplicates
+    pub fn totals(&self) -> (u64, u64, u64) {
+        let total = |counts: &BTreeMap<u32, u64>| counts.values().sum();
+        (total(&self.expanded), total(&self.generated), total(&self.duplicates))
+    }
+
+    // Write one line per g-value with the number of events of every kind
+    pub fn write_csv(&self, writer: &mut dyn Write) -> io::Result<()> {
+        writeln!(writer, "g,expanded,generated,duplicates")?;
+        let mut depths: Vec<u32> = self.expanded.keys().chain(self.generated.keys()).chain(self.duplicates.keys()).copied().collect();
+        depths.sort_unstable();
+        depths.dedup();
+        let count = |counts: &BTreeMap<u32, u64>, g: u32| counts.get(&g).copied().unwrap_or(0);
+        for g in depths {
+            writeln!(writer, "{},{},{},{}", g, count(&self.expanded, g), count(&self.generated, g), count(&self.duplicates, g))?;
+        }
+        Ok(())
+    }
+}
+
+impl SearchObserver for EventHistogram {
+    fn on_expand(&mut self, _board: &Board, g: u32, _h: u32) {
+        *self.expanded.entry(g).or_default() += 1;
+    }
+
+    fn on_generate(&mut self, _board: &Board, g: u32, _h: u32) {
+        *self.generated.entry(g).or_default() += 1;
+    }
+
+    fn on_duplicate(&mut self, _board: &Board, g: u32) {
+        *self.duplicates.entry(g).or_default() += 1;
+    }
+
+    fn on_iteration(&mut self, bound: u32, stats: &SearchStats) {
+        self.iterations.push((bound, stats.expanded));
+    }
+
+    fn on_solution(&mut self, moves: &[Move], _stats: &SearchStats) {
+        self.solution = Some(moves.len());
+    }
+}
+
+// Define a trace writing every event as one JSON object per line, numbered in order, e.g.
+// {"seq":3,"event":"expand","g":1,"h":5,"board":[1,2,3,4,5,6,7,0,8]}. Writing stops at the
+// first error, which `finish` returns. Traces of long searches grow large quickly.
+pub struct JsonTrace<W: Write> {
+    writer: W,
+    seq: u64,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> JsonTrace<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, seq: 0, error: None }
+    }
+
+    // Flush the trace and give back the writer, or the first error met
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write(&mut self, event: &str, fields: &str) {
+        if self.error.is_some() {
+            return;
+        }
+        self.seq += 1;
+        if let Err(error) = writeln!(self.writer, "{{\"seq\":{},\"event\":\"{}\",{}}}", self.seq, event, fields) {
+            self.error = Some(error);
+        }
+    }
+}
+
+fn board_json(board: &Board) -> String {
+    let tiles: Vec<String> = board.tiles().iter().map(|tile| tile.to_string()).collect();
+    format!("[{}]", tiles.join(","))
+}
+
+fn stats_json(stats: &SearchStats) -> String {
+    format!("\"expanded\":{},\"generated\":{}", stats.expanded, stats.generated)
+}
+
+impl<W: Write> SearchObserver for JsonTrace<W> {
+    fn on_expand(&mut self, board: &Board, g: u32, h: u32) {
+        self.write("expand", &format!("\"g\":{},\"h\":{},\"board\":{}", g, h, board_json(board)));
+    }
+
+    fn on_generate(&mut self, board: &Board, g: u32, h: u32) {
+        self.write("generate", &format!("\"g\":{},\"h\":{},\"board\":{}", g, h, board_json(board)));
+    }
+
+    fn on_duplicate(&mut self, board: &Board, g: u32) {
+        self.write("duplicate", &format!("\"g\":{},\"board\":{}", g, board_json(board)));
+    }
+
+    fn on_iteration(&mut self, bound: u32, stats: &SearchStats) {
+        self.write("iteration", &format!("\"bound\":{},{}", bound, stats_json(stats)));
+    }
+
+    fn on_progress(&mut self, bound: u32, stats: &SearchStats) {
+        self.write("progress", &format!("\"bound\":{},{}", bound, stats_json(stats)));
+    }
+
+    fn on_solution(&mut self, moves: &[Move], stats: &SearchStats) {
+        self.write("solution", &format!("\"length\":{},\"moves\":\"{}\",{}", moves.len(), moves_to_string(moves), stats_json(stats)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::cost::CostModel;
+    use crate::games::heuristics::manhattan_distance;
+    use crate::games::search::{self, SearchOptions};
+    use crate::games::test_support::{optimal_length, seeded_boards};
+
+    // Define an observer that stops the search after a number of expansions
+    struct StopAfter(usize);
+
+    impl SearchObserver for StopAfter {
+        fn on_expand(&mut self, _board: &Board, _g: u32, _h: u32) {
+            self.0 = self.0.saturating_sub(1);
+        }
+
+        fn should_stop(&mut self) -> bool {
+            self.0 == 0
+        }
+    }
+
+    // Define a writer that fails on every write
+    struct Broken;
+
+    impl Write for Broken {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("broken"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn histogram_totals_match_the_counters() {
+        let goal = Board::goal(3, 3);
+        for board in seeded_boards(10) {
+            let mut histogram = EventHistogram::default();
+            let solution = search::a_star_search_observed(&board, &goal, &manhattan_distance, &CostModel::Unit, &SearchOptions::default(), &mut histogram).unwrap();
+            let (expanded, generated, _) = histogram.totals();
+            assert_eq!((expanded, generated), (solution.stats.expanded as u64, solution.stats.generated as u64));
+            assert_eq!(histogram.solution, Some(optimal_length(&board) as usize));
+            assert!(histogram.iterations.is_empty());
+
+            let mut histogram = EventHistogram::default();
+            let solution = search::ida_star_search_observed(&board, &goal, &manhattan_distance, &CostModel::Unit, &SearchOptions::default(), &mut histogram).unwrap();
+            let (expanded, generated, _) = histogram.totals();
+            assert_eq!((expanded, generated), (solution.stats.expanded as u64, solution.stats.generated as u64));
+            assert_eq!(histogram.iterations.first(), Some(&(manhattan_distance(&board), 0)));
+            assert_eq!(histogram.iterations.last().map(|&(bound, _)| bound), Some(optimal_length(&board)));
+            let mut csv = vec![];
+            histogram.write_csv(&mut csv).unwrap();
+            assert_eq!(String::from_utf8(csv).unwrap().lines().count(), 1 + optimal_length(&board) as usize + 1);
+        }
+    }
+
+    #[test]
+    fn trace_writes_one_line_per_event() {
+        let goal = Board::goal(3, 3);
+        let board = seeded_boards(1).remove(0);
+        let mut histogram = EventHistogram::default();
+        let mut trace = JsonTrace::new(vec![]);
+        search::ida_star_search_observed(&board, &goal, &manhattan_distance, &CostModel::Unit, &SearchOptions::default(), &mut Broadcast(vec![&mut histogram, &mut trace])).unwrap();
+        let text = String::from_utf8(trace.finish().unwrap()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        let (expanded, generated, duplicates) = histogram.totals();
+        assert_eq!(lines.len() as u64, expanded + generated + duplicates + histogram.iterations.len() as u64 + 1);
+        for (i, line) in lines.iter().enumerate() {
+            assert!(line.starts_with(&format!("{{\"seq\":{},\"event\":\"", i + 1)) && line.ends_with('}'));
+        }
+        assert!(lines[0].contains("\"event\":\"iteration\""));
+        assert!(lines.last().unwrap().contains(&format!("\"event\":\"solution\",\"length\":{}", optimal_length(&board))));
+
+        let mut broken = JsonTrace::new(Broken);
+        search::a_star_search_observed(&board, &goal, &manhattan_distance, &CostModel::Unit, &SearchOptions::default(), &mut broken).unwrap();
+        assert!(broken.finish().is_err());
+    }
+
+    #[test]
+    fn broadcast_stops_when_any_observer_does() {
+        let goal = Board::goal(3, 3);
+        let board = seeded_boards(1).remove(0);
+        let mut histogram = EventHistogram::default();
+        let mut stop = StopAfter(5);
+        let mut observers = Broadcast(vec![&mut histogram, &mut stop]);
+        assert!(search::a_star_search_observed(&board, &goal, &manhattan_distance, &CostModel::Unit, &SearchOptions::default(), &mut observers).is_none());
+        assert_eq!(histogram.totals().0, 5);
+        assert_eq!(histogram.solution, None);
+        let mut stop = StopAfter(5);
+        assert!(search::ida_star_search_observed(&board, &goal, &manhattan_distance, &CostModel::Unit, &SearchOptions::default(), &mut stop).is_none());
+    }
+}
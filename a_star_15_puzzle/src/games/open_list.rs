@@ -0,0 +1,134 @@
+use std::collections::VecDeque;
+
+// Define the order in which entries with the same f-value leave the open list
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug)]
+pub enum TieBreaking {
+    // Prefer the entry with the larger g, the deepest one first among equals
+    #[default]
+    LargerG,
+    // Prefer the entry pushed last
+    Lifo,
+    // Prefer the entry pushed first
+    Fifo,
+}
+
+// Define the entries of one f-value, split by g when ties prefer the larger g
+struct Bucket<T> {
+    by_g: Vec<VecDeque<(u32, T)>>,
+    // Highest g slot that may be non-empty
+    top: usize,
+    len: usize,
+}
+
+impl<T> Bucket<T> {
+    fn new() -> Self {
+        Self { by_g: vec![], top: 0, len: 0 }
+    }
+}
+
+// Define an open list for integer f-values: one bucket per f-value, so pushing and popping take
+// constant time (amortised over the scan for the next non-empty bucket) instead of O(log n)
+pub struct BucketOpenList<T> {
+    buckets: Vec<Bucket<T>>,
+    // Lowest f-value that may have entries
+    min_f: usize,
+    len: usize,
+    tie_breaking: TieBreaking,
+}
+
+impl<T> BucketOpenList<T> {
+    pub fn new(tie_breaking: TieBreaking) -> Self {
+        Self { buckets: vec![], min_f: 0, len: 0, tie_breaking }
+    }
+
+    pub fn push(&mut self, f: u32, g: u32, item: T) {
+        let f = f as usize;
+        if self.buckets.len() <= f {
+            self.buckets.resize_with(f + 1, Bucket::new);
+        }
+        let slot = if self.tie_breaking == TieBreaking::LargerG { g as usize } else { 0 };
+        let bucket = &mut self.buckets[f];
+        if bucket.by_g.len() <= slot {
+            bucket.by_g.resize_with(slot + 1, VecDeque::new);
+        }
+        bucket.by_g[slot].push_back((g, item));
+        bucket.top = bucket.top.max(slot);
+        bucket.len += 1;
+        // Inconsistent heuristics can push below the current minimum
+        self.min_f = self.min_f.min(f);
+        self.len += 1;
+    }
+
+    // Number of entries held, including ones the caller will skip as stale
+    pub fn entries(&self) -> usize {
+        self.len
+    }
+
+    // Take the entry with the smallest f-value, returning it with its f and g
+    pub fn pop(&mut self) -> Option<(u32, u32, T)> {
+        if self.len == 0 {
+            return None;
+        }
+        while self.buckets[self.min_f].len == 0 {
+            self.min_f += 1;
+        }
+        let bucket = &mut self.buckets[self.min_f];
+        while bucket.by_g[bucket.top].is_empty() {
+            bucket.top -= 1;
+        }
+        let entries = &mut bucket.by_g[bucket.top];
+        let (g, item) = match self.tie_breaking {
+            TieBreaking::LargerG | TieBreaking::Lifo => entries.pop_back(),
+            TieBreaking::Fifo => entries.pop_front(),
+        }?;
+        bucket.len -= 1;
+        self.len -= 1;
+        Some((self.min_f as u32, g, item))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Pop every entry pushed as (f, g, name), returning the names in order
+    fn popped(tie_breaking: TieBreaking, entries: &[(u32, u32, char)]) -> String {
+        let mut open = BucketOpenList::new(tie_breaking);
+        for &(f, g, name) in entries {
+            open.push(f, g, name);
+        }
+        assert_eq!(open.entries(), entries.len());
+        let mut names = String::new();
+        while let Some((f, g, name)) = open.pop() {
+            assert!(entries.contains(&(f, g, name)));
+            names.push(name);
+        }
+        assert_eq!(open.entries(), 0);
+        names
+    }
+
+    const ENTRIES: [(u32, u32, char); 6] = [(5, 1, 'a'), (4, 2, 'b'), (5, 3, 'c'), (4, 0, 'd'), (5, 3, 'e'), (4, 2, 'f')];
+
+    #[test]
+    fn ties_prefer_the_larger_g() {
+        assert_eq!(popped(TieBreaking::LargerG, &ENTRIES), "fbdeca");
+    }
+
+    #[test]
+    fn ties_follow_the_push_order() {
+        assert_eq!(popped(TieBreaking::Lifo, &ENTRIES), "fdbeca");
+        assert_eq!(popped(TieBreaking::Fifo, &ENTRIES), "bdface");
+    }
+
+    #[test]
+    fn pushes_below_the_minimum_pop_first() {
+        let mut open = BucketOpenList::new(TieBreaking::default());
+        open.push(6, 1, 'a');
+        open.push(7, 1, 'b');
+        assert_eq!(open.pop(), Some((6, 1, 'a')));
+        open.push(3, 4, 'c');
+        assert_eq!(open.pop(), Some((3, 4, 'c')));
+        assert_eq!(open.pop(), Some((7, 1, 'b')));
+        assert_eq!(open.pop(), None);
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use crate::games::search;
+use crate::games::sliding::{moves_to_string, Board, Move, MOVES};
+
+// Define the number of distinct optimal solutions of an instance
+#[derive(Clone, Copy, Debug)]
+pub struct OptimalCount {
+    pub length: u32,
+    pub count: u64,
+}
+
+// Count every optimal solution: IDA* finds the optimal length, then one more depth-first pass at
+// that bound counts the paths reaching the goal. An optimal path never visits a board twice, so
+// the number of completions of a board only depends on the moves left and can be memoised.
+pub fn count_optimal_solutions(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32) -> Option<OptimalCount> {
+    let length = search::ida_star_search(start, goal, heuristic)?.moves.len() as u32;
+    let mut board = start.clone();
+    let mut memo = HashMap::new();
+    let count = count_paths(&mut board, goal, None, 0, length, heuristic, &mut memo);
+    Some(OptimalCount { length, count })
+}
+
+fn count_paths(board: &mut Board, goal: &Board, last: Option<Move>, g: u32, bound: u32, heuristic: &dyn Fn(&Board) -> u32, memo: &mut HashMap<(Board, u32), u64>) -> u64 {
+    if g + heuristic(board) > bound {
+        return 0;
+    }
+    if g == bound {
+        return (board == goal) as u64;
+    }
+    if let Some(&count) = memo.get(&(board.clone(), g)) {
+        return count;
+    }
+    let mut count = 0;
+    for m in MOVES {
+        if last == Some(m.opposite()) || !board.apply(m) {
+            continue;
+        }
+        count += count_paths(board, goal, Some(m), g + 1, bound, heuristic, memo);
+        board.apply(m.opposite());
+    }
+    memo.insert((board.clone(), g), count);
+    count
+}
+
+// Define a lazy iterator over all optimal solutions in move notation, stopping after `cap` of them
+pub struct OptimalSolutions<'a> {
+    board: Board,
+    goal: Board,
+    heuristic: &'a dyn Fn(&Board) -> u32,
+    bound: u32,
+    path: Vec<Move>,
+    // Index of the next move to try at every depth of the current path
+    next_move: Vec<usize>,
+    cap: Option<usize>,
+    yielded: usize,
+}
+
+impl<'a> OptimalSolutions<'a> {
+    pub fn new(start: &Board, goal: &Board, heuristic: &'a dyn Fn(&Board) -> u32, cap: Option<usize>) -> Option<Self> {
+        let bound = search::ida_star_search(start, goal, heuristic)?.moves.len() as u32;
+        Some(Self {
+            board: start.clone(),
+            goal: goal.clone(),
+            heuristic,
+            bound,
+            path: vec![],
+            next_move: vec![0],
+            cap,
+            yielded: 0,
+        })
+    }
+}
+
+impl<'a> Iterator for OptimalSolutions<'a> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.cap.is_some_and(|cap| self.yielded >= cap) {
+            return None;
+        }
+        if self.bound == 0 {
+            // The start is the goal: the empty solution is the only one
+            if self.next_move.pop().is_some() {
+                self.yielded += 1;
+                return Some(String::new());
+            }
+            return None;
+        }
+        while let Some(index) = self.next_move.last_mut() {
+            if *index >= MOVES.len() {
+                // Every move from this board was tried, step back to the parent
+                self.next_move.pop();
+                if let Some(m) = self.path.pop() {
+                    self.board.apply(m.opposite());
+                }
+                continue;
+            }
+            let m = MOVES[*index];
+            *index += 1;
+            if self.path.last() == Some(&m.opposite()) || !self.board.apply(m) {
+                continue;
+            }
+            let g = self.path.len() as u32 + 1;
+            if g + (self.heuristic)(&self.board) > self.bound {
+                self.board.apply(m.opposite());
+                continue;
+            }
+            self.path.push(m);
+            if g == self.bound {
+                let solution = (self.board == self.goal).then(|| moves_to_string(&self.path));
+                self.path.pop();
+                self.board.apply(m.opposite());
+                if let Some(solution) = solution {
+                    self.yielded += 1;
+                    return Some(solution);
+                }
+                continue;
+            }
+            self.next_move.push(0);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::heuristics::manhattan_distance;
+    use crate::games::sliding;
+    use crate::games::test_support::{optimal_length, played, seeded_boards};
+    use std::collections::HashSet;
+
+    fn parse(solution: &str) -> Vec<Move> {
+        solution
+            .chars()
+            .map(|c| *MOVES.iter().find(|m| m.to_char() == c).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn count_matches_the_enumerated_solutions() {
+        let goal = Board::goal(3, 3);
+        for start in seeded_boards(10).iter().chain([&goal]) {
+            let count = count_optimal_solutions(start, &goal, &manhattan_distance).unwrap();
+            assert_eq!(count.length, optimal_length(start));
+            let solutions: Vec<String> = OptimalSolutions::new(start, &goal, &manhattan_distance, None).unwrap().collect();
+            assert_eq!(solutions.len() as u64, count.count);
+            assert_eq!(solutions.iter().collect::<HashSet<_>>().len(), solutions.len());
+            for solution in &solutions {
+                let moves = parse(solution);
+                assert_eq!(moves.len() as u32, count.length);
+                assert_eq!(played(start, &moves), Some(goal.clone()));
+            }
+        }
+    }
+
+    #[test]
+    fn enumeration_stops_at_the_cap() {
+        let goal = Board::goal(3, 3);
+        let start = sliding::seeded_random_board(3, 3, 1);
+        let count = count_optimal_solutions(&start, &goal, &manhattan_distance).unwrap().count;
+        let cap = (count as usize).min(3);
+        assert_eq!(OptimalSolutions::new(&start, &goal, &manhattan_distance, Some(cap)).unwrap().count(), cap);
+    }
+}
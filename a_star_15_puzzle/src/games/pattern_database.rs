@@ -0,0 +1,308 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::games::cost::CostModel;
+use crate::games::heuristics::Heuristic;
+use crate::games::sliding::Board;
+
+// Define the board shape a pattern database is built for, and the ranking of the cells of a
+// group of tiles as a partial permutation of the board cells
+#[derive(Clone, Copy)]
+pub struct Layout {
+    rows: usize,
+    cols: usize,
+    cells: usize,
+}
+
+impl Layout {
+    pub fn new(goal: &Board) -> Self {
+        let (rows, cols) = (goal.rows(), goal.cols());
+        assert!(rows * cols <= 64, "pattern databases support boards of at most 64 cells");
+        Self { rows, cols, cells: rows * cols }
+    }
+
+    // Number of ways to place the pattern tiles on the board
+    pub fn size(&self, tiles: usize) -> usize {
+        (self.cells - tiles + 1..=self.cells).product()
+    }
+
+    // Rank the cells of the pattern tiles as a partial permutation of the board cells
+    pub fn rank(&self, positions: impl Iterator<Item = usize>) -> usize {
+        let mut index = 0;
+        let mut used = 0u64;
+        for (i, cell) in positions.enumerate() {
+            let smaller_used = (used & ((1u64 << cell) - 1)).count_ones() as usize;
+            index = index * (self.cells - i) + cell - smaller_used;
+            used |= 1 << cell;
+        }
+        index
+    }
+
+    pub fn unrank(&self, mut index: usize, positions: &mut [usize]) {
+        for i in (0..positions.len()).rev() {
+            positions[i] = index % (self.cells - i);
+            index /= self.cells - i;
+        }
+        let mut used = 0u64;
+        for position in positions.iter_mut() {
+            // Turn the digit into the digit-th cell that is still free
+            let mut digit = *position;
+            let mut cell = 0;
+            loop {
+                if used & 1 << cell == 0 {
+                    if digit == 0 {
+                        break;
+                    }
+                    digit -= 1;
+                }
+                cell += 1;
+            }
+            *position = cell;
+            used |= 1 << cell;
+        }
+    }
+
+    // Call `visit` with the pattern tile that moved and the rank of the result, for every move
+    // of one pattern tile onto a free neighbouring cell
+    fn successors(&self, index: usize, positions: &mut [usize], visit: &mut dyn FnMut(usize, usize)) {
+        let (rows, cols) = (self.rows, self.cols);
+        self.unrank(index, positions);
+        let occupied = positions.iter().fold(0u64, |mask, &cell| mask | 1 << cell);
+        for i in 0..positions.len() {
+            let cell = positions[i];
+            let (row, col) = (cell / cols, cell % cols);
+            let neighbours = [
+                (row > 0).then(|| cell - cols),
+                (row + 1 < rows).then(|| cell + cols),
+                (col > 0).then(|| cell - 1),
+                (col + 1 < cols).then(|| cell + 1),
+            ];
+            for neighbour in neighbours.into_iter().flatten() {
+                if occupied & 1 << neighbour == 0 {
+                    positions[i] = neighbour;
+                    visit(i, self.rank(positions.iter().copied()));
+                }
+            }
+            positions[i] = cell;
+        }
+    }
+
+    fn home(&self, goal: &Board, pattern: &[u16]) -> usize {
+        self.rank(pattern.iter().map(|&tile| goal.tiles().iter().position(|&t| t == tile).expect("pattern tile is on the board")))
+    }
+}
+
+// Define a pattern database for a group of tiles: the cost of the moves of those tiles needed
+// to bring them home, ignoring every other tile and the blank. Since only moves of pattern tiles
+// are counted, databases over disjoint groups can be added together. Unit costs fit in a byte,
+// weighted costs are stored in four.
+pub struct PatternDatabase<D = u8> {
+    layout: Layout,
+    pattern: Vec<u16>,
+    distances: Vec<D>,
+}
+
+impl<D: Copy + Into<u32>> PatternDatabase<D> {
+    // Look up the pattern distance given the cell of every tile, indexed by tile
+    pub fn lookup(&self, cell_of: &[usize]) -> u32 {
+        self.distances[self.layout.rank(self.pattern.iter().map(|&tile| cell_of[tile as usize]))].into()
+    }
+}
+
+impl PatternDatabase<u8> {
+    // Build the database with a breadth-first search backwards from the goal positions
+    pub fn build(goal: &Board, pattern: &[u16]) -> Self {
+        let layout = Layout::new(goal);
+        let mut distances = vec![u8::MAX; layout.size(pattern.len())];
+        let start = layout.home(goal, pattern);
+        distances[start] = 0;
+        let mut layer = vec![start as u32];
+        let mut depth = 0;
+        let mut positions = vec![0; pattern.len()];
+        while !layer.is_empty() {
+            depth += 1;
+            let mut next = vec![];
+            for &index in &layer {
+                layout.successors(index as usize, &mut positions, &mut |_, successor| {
+                    if distances[successor] == u8::MAX {
+                        distances[successor] = depth;
+                        next.push(successor as u32);
+                    }
+                });
+            }
+            layer = next;
+        }
+        Self { layout, pattern: pattern.to_vec(), distances }
+    }
+
+    // Load the database from `dir` if it was built before, otherwise build and save it there
+    pub fn load_or_build(goal: &Board, pattern: &[u16], dir: &Path) -> io::Result<Self> {
+        let homes: Vec<String> = pattern
+            .iter()
+            .map(|&tile| format!("{}@{}", tile, goal.tiles().iter().position(|&t| t == tile).unwrap_or(0)))
+            .collect();
+        let path = dir.join(format!("pdb_{}x{}_{}.bin", goal.rows(), goal.cols(), homes.join("_")));
+        let layout = Layout::new(goal);
+        if let Ok(distances) = fs::read(&path) {
+            if distances.len() == layout.size(pattern.len()) {
+                return Ok(Self { layout, pattern: pattern.to_vec(), distances });
+            }
+        }
+        let database = Self::build(goal, pattern);
+        fs::create_dir_all(dir)?;
+        fs::write(&path, &database.distances)?;
+        Ok(database)
+    }
+}
+
+impl PatternDatabase<u32> {
+    // Build the database for a cost model with Dijkstra's algorithm. Fails when a pattern
+    // distance does not fit in 32 bits.
+    pub fn build_weighted(goal: &Board, pattern: &[u16], costs: &CostModel) -> io::Result<Self> {
+        let layout = Layout::new(goal);
+        let mut distances = vec![u32::MAX; layout.size(pattern.len())];
+        let tile_costs: Vec<u32> = pattern.iter().map(|&tile| costs.tile_cost(tile)).collect();
+        let start = layout.home(goal, pattern);
+        distances[start] = 0;
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse((0, start as u32)));
+        let mut positions = vec![0; pattern.len()];
+        let mut overflow = false;
+        while let Some(Reverse((cost, index))) = queue.pop() {
+            // Skip entries that were reached more cheaply after they were queued
+            if distances[index as usize] != cost {
+                continue;
+            }
+            layout.successors(index as usize, &mut positions, &mut |tile, successor| match cost.checked_add(tile_costs[tile]) {
+                Some(distance) if distance < distances[successor] => {
+                    distances[successor] = distance;
+                    queue.push(Reverse((distance, successor as u32)));
+                }
+                Some(_) => {}
+                None => overflow = true,
+            });
+            if overflow {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "pattern database costs do not fit in 32 bits"));
+            }
+        }
+        Ok(Self { layout, pattern: pattern.to_vec(), distances })
+    }
+}
+
+// Define the cell of every tile, indexed by tile
+pub fn cells_of(board: &Board) -> Vec<usize> {
+    let mut cell_of = vec![0; board.tiles().len()];
+    for (cell, &tile) in board.tiles().iter().enumerate() {
+        cell_of[tile as usize] = cell;
+    }
+    cell_of
+}
+
+// Define an additive heuristic over disjoint pattern databases
+pub struct AdditivePatternDatabase<D = u8> {
+    databases: Vec<PatternDatabase<D>>,
+}
+
+impl<D: Copy + Into<u32>> AdditivePatternDatabase<D> {
+    pub fn new(databases: Vec<PatternDatabase<D>>) -> Self {
+        Self { databases }
+    }
+
+    pub fn estimate(&self, board: &Board) -> u32 {
+        let cell_of = cells_of(board);
+        self.databases.iter().map(|database| database.lookup(&cell_of)).sum()
+    }
+
+    // Define the change of the estimate when `tile` moves to cell `to`, which only needs the
+    // database holding that tile
+    pub fn move_delta(&self, cell_of: &mut [usize], tile: u16, to: usize) -> i32 {
+        let Some(database) = self.databases.iter().find(|database| database.pattern.contains(&tile)) else {
+            return 0;
+        };
+        let before = database.lookup(cell_of) as i32;
+        let from = std::mem::replace(&mut cell_of[tile as usize], to);
+        let after = database.lookup(cell_of) as i32;
+        cell_of[tile as usize] = from;
+        after - before
+    }
+}
+
+impl<D: Copy + Into<u32>> Heuristic for PatternDatabase<D> {
+    fn name(&self) -> String {
+        let tiles: Vec<String> = self.pattern.iter().map(|tile| tile.to_string()).collect();
+        format!("PDB {{{}}}", tiles.join(", "))
+    }
+
+    fn estimate(&self, board: &Board) -> u32 {
+        self.lookup(&cells_of(board))
+    }
+
+    fn is_admissible(&self) -> bool {
+        true
+    }
+
+    fn additive_tiles(&self) -> Option<Vec<u16>> {
+        Some(self.pattern.clone())
+    }
+}
+
+impl<D: Copy + Into<u32>> Heuristic for AdditivePatternDatabase<D> {
+    fn name(&self) -> String {
+        let names: Vec<String> = self.databases.iter().map(|database| database.name()).collect();
+        names.join(" + ")
+    }
+
+    fn estimate(&self, board: &Board) -> u32 {
+        AdditivePatternDatabase::estimate(self, board)
+    }
+
+    fn is_admissible(&self) -> bool {
+        true
+    }
+
+    fn additive_tiles(&self) -> Option<Vec<u16>> {
+        Some(self.databases.iter().flat_map(|database| database.pattern.iter().copied()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::test_support::{optimal_length, seeded_boards};
+
+    #[test]
+    fn rank_and_unrank_round_trip() {
+        let layout = Layout::new(&Board::goal(3, 4));
+        let mut positions = vec![0; 4];
+        for index in 0..layout.size(4) {
+            layout.unrank(index, &mut positions);
+            assert_eq!(layout.rank(positions.iter().copied()), index);
+        }
+    }
+
+    #[test]
+    fn additive_databases_are_admissible() {
+        let goal = Board::goal(3, 3);
+        let databases = AdditivePatternDatabase::new(vec![PatternDatabase::build(&goal, &[1, 2, 3, 4]), PatternDatabase::build(&goal, &[5, 6, 7, 8])]);
+        assert_eq!(databases.estimate(&goal), 0);
+        for board in seeded_boards(50) {
+            assert!(databases.estimate(&board) <= optimal_length(&board));
+        }
+    }
+
+    #[test]
+    fn weighted_databases_keep_costs_above_two_bytes() {
+        let goal = Board::goal(3, 3);
+        let unit = PatternDatabase::build(&goal, &[1, 2, 3, 4]);
+        let costs = CostModel::Table(vec![100_000; 9]);
+        let weighted = PatternDatabase::build_weighted(&goal, &[1, 2, 3, 4], &costs).unwrap();
+        for board in seeded_boards(20) {
+            assert_eq!(weighted.estimate(&board), 100_000 * unit.estimate(&board));
+        }
+        let too_costly = CostModel::Table(vec![u32::MAX / 2; 9]);
+        assert_eq!(PatternDatabase::build_weighted(&goal, &[1, 2, 3, 4], &too_costly).err().map(|error| error.kind()), Some(io::ErrorKind::InvalidInput));
+    }
+}
@@ -0,0 +1,277 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use dialoguer::console::{Key, Term};
+
+use crate::games::heuristics::{Heuristic, LinearConflict, MaxOf, WalkingDistance};
+use crate::games::render;
+use crate::games::search;
+use crate::games::sliding::{self, Board, Move};
+
+// Define a game played by hand: the board, the moves played so far and, once known, the
+// optimal moves left from the current board. The plan survives as long as the player follows
+// it, so hints along an optimal line cost one search.
+pub struct Game {
+    board: Board,
+    goal: Board,
+    heuristic: MaxOf,
+    history: Vec<Move>,
+    optimum: u32,
+    // Optimal moves from the current board, the next one last
+    plan: Vec<Move>,
+}
+
+impl Game {
+    // Start a game on a solvable board, solving it once to know the optimum. IDA* with the
+    // larger of linear conflicts and walking distance handles any 8-puzzle and most 15-puzzles
+    // in seconds, the deepest 15-puzzles take longer.
+    pub fn new(start: Board) -> Self {
+        let goal = Board::goal(start.rows(), start.cols());
+        let heuristic = MaxOf::new(vec![Box::new(LinearConflict::new(&goal)), Box::new(WalkingDistance::new(&goal))]);
+        let mut game = Self { board: start, goal, heuristic, history: vec![], optimum: 0, plan: vec![] };
+        game.plan = game.solve();
+        game.optimum = game.plan.len() as u32;
+        game
+    }
+
+    fn solve(&self) -> Vec<Move> {
+        let solution = search::ida_star_search(&self.board, &self.goal, &|board| self.heuristic.estimate(board)).expect("board is solvable");
+        solution.moves.into_iter().rev().collect()
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn moves(&self) -> usize {
+        self.history.len()
+    }
+
+    // Optimal solution length of the start board
+    pub fn optimum(&self) -> u32 {
+        self.optimum
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.board == self.goal
+    }
+
+    // Move the blank, returning false if it would leave the board
+    pub fn play(&mut self, m: Move) -> bool {
+        if !self.board.apply(m) {
+            return false;
+        }
+        self.history.push(m);
+        if self.plan.last() == Some(&m) {
+            self.plan.pop();
+        } else {
+            self.plan.clear();
+        }
+        true
+    }
+
+    // Take back the last move, returning it
+    pub fn undo(&mut self) -> Option<Move> {
+        let m = self.history.pop()?;
+        self.board.apply(m.opposite());
+        self.plan.clear();
+        Some(m)
+    }
+
+    // Next move of an optimal solution from the current board and the optimal distance left,
+    // None once solved
+    pub fn hint(&mut self) -> Option<(Move, usize)> {
+        if self.is_solved() {
+            return None;
+        }
+        if self.plan.is_empty() {
+            self.plan = self.solve();
+        }
+        self.plan.last().map(|&m| (m, self.plan.len()))
+    }
+}
+
+// Load an 8- or 15-puzzle from a text file with one row per line and tiles separated by spaces,
+// 0 being the blank. Empty lines and lines starting with '#' are skipped.
+pub fn load_board(path: &Path) -> io::Result<Board> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let mut rows = vec![];
+    for line in fs::read_to_string(path)?.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let row = line
+            .split_whitespace()
+            .map(|tile| tile.parse::<u16>())
+            .collect::<Result<Vec<u16>, _>>()
+            .map_err(|_| invalid("tiles must be numbers"))?;
+        rows.push(row);
+    }
+    let cols = rows.first().map_or(0, |row| row.len());
+    if rows.iter().any(|row| row.len() != cols) {
+        return Err(invalid("expected rows of the same length"));
+    }
+    // Larger boards would keep the solver busy for too long before play starts
+    if !matches!((rows.len(), cols), (3, 3) | (4, 4)) {
+        return Err(invalid("expected a 3x3 or 4x4 board"));
+    }
+    let tiles: Vec<u16> = rows.concat();
+    let mut sorted = tiles.clone();
+    sorted.sort_unstable();
+    if sorted.iter().enumerate().any(|(i, &tile)| tile as usize != i) {
+        return Err(invalid("expected the tiles 0..rows*cols-1"));
+    }
+    let board = Board::from_tiles(rows.len(), cols, tiles);
+    if !sliding::is_solvable(&board) {
+        return Err(invalid("the board cannot be solved"));
+    }
+    Ok(board)
+}
+
+// The arrow keys slide the tile next to the blank in their direction, so the blank moves the
+// other way
+fn key_move(key: &Key) -> Option<Move> {
+    match key {
+        Key::ArrowUp => Some(Move::Down),
+        Key::ArrowDown => Some(Move::Up),
+        Key::ArrowLeft => Some(Move::Right),
+        Key::ArrowRight => Some(Move::Left),
+        _ => None,
+    }
+}
+
+// Describe a move of the blank as the slide of the tile it swaps with
+fn describe(board: &Board, m: Move) -> String {
+    let direction = match m {
+        Move::Up => "down",
+        Move::Down => "up",
+        Move::Left => "right",
+        Move::Right => "left",
+    };
+    format!("slide {} {}", board.tile_moved_by(m).unwrap_or(0), direction)
+}
+
+fn status(game: &Game) -> String {
+    format!("moves {}, optimum from the start {}", game.moves(), game.optimum())
+}
+
+// Let a person play the game on the terminal: the arrow keys slide tiles, h shows the next
+// optimal move, u or backspace undoes a move and q leaves
+pub fn run(term: &Term, game: &mut Game) -> io::Result<()> {
+    if !term.is_term() {
+        term.write_line("Playing needs a terminal.")?;
+        return Ok(());
+    }
+    term.hide_cursor()?;
+    let mut message = String::new();
+    let mut highlight = None;
+    let mut drawn = 0;
+    loop {
+        term.clear_last_lines(drawn)?;
+        let text = if game.is_solved() {
+            let verdict = if game.moves() as u32 == game.optimum() { ", an optimal solution" } else { "" };
+            format!("{}\nSolved in {} moves, the optimum is {}{}", render::render(game.board(), highlight), game.moves(), game.optimum(), verdict)
+        } else {
+            format!(
+                "{}\n{}\n{}\n[arrows] slide  [h] hint  [u] undo  [q] quit",
+                render::render(game.board(), highlight),
+                status(game),
+                message
+            )
+        };
+        drawn = text.lines().count();
+        term.write_line(&text)?;
+        if game.is_solved() {
+            break;
+        }
+        let key = term.read_key()?;
+        message.clear();
+        match key {
+            Key::Char('q') | Key::Escape => break,
+            Key::Char('h') => {
+                if let Some((m, left)) = game.hint() {
+                    message = format!("Hint: {} ({} moves left)", describe(game.board(), m), left);
+                }
+            }
+            Key::Char('u') | Key::Backspace => {
+                highlight = game.undo().and_then(|m| game.board().tile_moved_by(m));
+                if highlight.is_none() {
+                    message = "Nothing to undo.".to_string();
+                }
+            }
+            key => {
+                if let Some(m) = key_move(&key) {
+                    let tile = game.board().tile_moved_by(m);
+                    if game.play(m) {
+                        highlight = tile;
+                    } else {
+                        message = "No tile to slide that way.".to_string();
+                    }
+                }
+            }
+        }
+    }
+    term.show_cursor()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::test_support::{optimal_length, seeded_boards, temporary_path};
+
+    #[test]
+    fn hints_lead_to_the_goal() {
+        for board in seeded_boards(10) {
+            let mut game = Game::new(board.clone());
+            assert_eq!(game.optimum(), optimal_length(&board));
+            while let Some((m, left)) = game.hint() {
+                assert_eq!(left as u32, optimal_length(game.board()));
+                assert!(game.play(m));
+            }
+            assert!(game.is_solved());
+            assert_eq!(game.moves() as u32, game.optimum());
+        }
+    }
+
+    #[test]
+    fn undo_takes_moves_back() {
+        let board = seeded_boards(1).remove(0);
+        let mut game = Game::new(board.clone());
+        let (hint, _) = game.hint().unwrap();
+        // Stray from the plan and come back
+        let stray = board.successors().into_iter().map(|(m, _)| m).find(|&m| m != hint).unwrap();
+        assert!(game.play(stray));
+        assert_eq!(game.hint().map(|(_, left)| left as u32), Some(optimal_length(game.board())));
+        assert_eq!(game.undo(), Some(stray));
+        assert_eq!(game.board(), &board);
+        assert_eq!(game.moves(), 0);
+        assert_eq!(game.undo(), None);
+        assert_eq!(game.hint().map(|(m, _)| m), Some(hint));
+    }
+
+    #[test]
+    fn moves_off_the_board_are_refused() {
+        let mut game = Game::new(Board::from([[1, 2, 3], [4, 5, 6], [7, 0, 8]]));
+        assert!(!game.play(Move::Down));
+        assert_eq!(game.moves(), 0);
+        assert!(game.play(Move::Right));
+        assert!(game.is_solved());
+        assert_eq!(game.hint(), None);
+    }
+
+    #[test]
+    fn board_files_are_checked() {
+        let path = temporary_path("play_board.txt");
+        let load = |text: &str| {
+            fs::write(&path, text).unwrap();
+            load_board(&path)
+        };
+        assert_eq!(load("# a comment\n1 2 3\n\n4 5 6\n7 0 8\n").unwrap(), Board::from([[1, 2, 3], [4, 5, 6], [7, 0, 8]]));
+        for text in ["1 2 3\n4 5 6\n7 x 8", "1 2 3\n4 5\n6 7 8 0", "1 2 3 0", "1 2 3\n4 5 6\n7 7 0", "1 2 3\n4 5 6\n8 7 0"] {
+            assert_eq!(load(text).err().map(|error| error.kind()), Some(io::ErrorKind::InvalidData), "{:?}", text);
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,113 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::games::pattern_database::{AdditivePatternDatabase, PatternDatabase};
+use crate::games::search::{self, SearchStats, Solution};
+use crate::games::sliding::{self, Board};
+
+// Define the size of the 24 puzzle
+pub const ROWS: usize = 5;
+pub const COLS: usize = 5;
+
+// Define the default goal state, with the blank in the bottom right corner
+pub fn goal() -> Board {
+    Board::goal(ROWS, COLS)
+}
+
+// Define the goal used by the published benchmark instances, with the blank in the top left corner
+pub fn blank_first_goal() -> Board {
+    Board::from_tiles(ROWS, COLS, (0..(ROWS * COLS) as u16).collect())
+}
+
+//Shuffle state with Fisher–Yates shuffle
+pub fn random_state() -> Board {
+    sliding::random_board(ROWS, COLS)
+}
+
+//Shuffle state reproducibly from a seed
+pub fn seeded_state(seed: u64) -> Board {
+    sliding::seeded_random_board(ROWS, COLS, seed)
+}
+
+//Shuffle state reproducibly with n random moves from the goal
+pub fn seeded_n_random_moves_from_goal(n: usize, seed: u64) -> Board {
+    sliding::seeded_n_random_moves_from_goal(ROWS, COLS, n, seed)
+}
+
+// Split the tiles into four groups of six: the four 2x3 blocks turning around the centre cell.
+// The block holding the blank in the goal takes the tile of the centre cell instead.
+pub fn six_six_six_six_partition(goal: &Board) -> Vec<Vec<u16>> {
+    let blocks: [(usize, usize, usize, usize); 4] = [(0, 2, 0, 3), (0, 3, 3, 5), (3, 5, 2, 5), (2, 5, 0, 2)];
+    let centre = goal.tiles()[2 * COLS + 2];
+    blocks
+        .iter()
+        .map(|&(top, bottom, left, right)| {
+            let mut group: Vec<u16> = (top..bottom)
+                .flat_map(|row| (left..right).map(move |col| goal.tiles()[row * COLS + col]))
+                .collect();
+            if let Some(blank) = group.iter().position(|&tile| tile == 0) {
+                group[blank] = centre;
+            }
+            group
+        })
+        .collect()
+}
+
+// Load the 6-6-6-6 pattern databases for the goal from `dir`, building the missing ones.
+// Every database holds 25*24*23*22*21*20 bytes and takes a couple of minutes to build.
+pub fn pattern_databases(goal: &Board, dir: &Path, on_build: &mut dyn FnMut(usize, &[u16])) -> io::Result<AdditivePatternDatabase> {
+    let mut databases = vec![];
+    for (i, pattern) in six_six_six_six_partition(goal).iter().enumerate() {
+        on_build(i, pattern);
+        databases.push(PatternDatabase::load_or_build(goal, pattern, dir)?);
+    }
+    Ok(AdditivePatternDatabase::new(databases))
+}
+
+// Load benchmark instances, one per line as 25 tiles with 0 as the blank. A leading instance
+// number is skipped, as are empty lines and lines starting with '#'.
+pub fn load_instances(path: &Path) -> io::Result<Vec<Board>> {
+    let mut instances = vec![];
+    for (number, line) in fs::read_to_string(path)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", number + 1, message));
+        let mut tiles = line
+            .split_whitespace()
+            .map(|tile| tile.parse::<u16>())
+            .collect::<Result<Vec<u16>, _>>()
+            .map_err(|_| invalid("tiles must be numbers"))?;
+        if tiles.len() == ROWS * COLS + 1 {
+            tiles.remove(0);
+        }
+        let mut sorted = tiles.clone();
+        sorted.sort_unstable();
+        if sorted.len() != ROWS * COLS || sorted.iter().enumerate().any(|(i, &tile)| tile as usize != i) {
+            return Err(invalid("expected the tiles 0..24"));
+        }
+        instances.push(Board::from_tiles(ROWS, COLS, tiles));
+    }
+    Ok(instances)
+}
+
+// Define the IDA* search with the additive pattern databases, reporting progress as it goes
+pub fn ida_star_search(start: &Board, goal: &Board, databases: &AdditivePatternDatabase, progress: &mut dyn FnMut(u32, &SearchStats)) -> Option<Solution> {
+    search::ida_star_search_with_progress(start, goal, &|board| databases.estimate(board), progress)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partition_covers_every_tile_once() {
+        for goal in [goal(), blank_first_goal()] {
+            let mut tiles: Vec<u16> = six_six_six_six_partition(&goal).concat();
+            tiles.sort_unstable();
+            assert_eq!(tiles, (1..25).collect::<Vec<u16>>());
+        }
+    }
+}
@@ -1,20 +1,34 @@
-use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashSet};
+use std::collections::HashSet;
 use rand::prelude::*;
 use rand_pcg::Pcg64;
 
-// Define the goal state
-const GOAL_STATE: [[u8; 3]; 3] = [[1, 2, 3], [4, 5, 6], [7, 8, 0]];
+use crate::games::heuristics::linear_conflict_penalty;
+use crate::games::open_list::{BucketOpenList, TieBreaking};
+use crate::games::sliding::{is_solvable_towards, Board};
+
+// Define the default goal state
+pub const GOAL_STATE: [[u8; 3]; 3] = [[1, 2, 3], [4, 5, 6], [7, 8, 0]];
+
+// Find the (row, column) of every tile in the goal state
+fn goal_positions(goal: &[[u8; 3]; 3]) -> [(usize, usize); 9] {
+    let mut positions = [(0, 0); 9];
+    for i in 0..3 {
+        for j in 0..3 {
+            positions[goal[i][j] as usize] = (i, j);
+        }
+    }
+    positions
+}
 
 // Define the Manhattan distance heuristic function
-fn manhattan_distance(state: &[[u8; 3]; 3]) -> u8 {
+fn manhattan_distance(state: &[[u8; 3]; 3], goal: &[[u8; 3]; 3]) -> u8 {
+    let positions = goal_positions(goal);
     let mut distance = 0;
     for i in 0..3 {
         for j in 0..3 {
             if state[i][j] != 0 {
-                let x = (state[i][j] - 1) / 3;
-                let y = (state[i][j] - 1) % 3;
-                distance += (i as i8 - x as i8).abs() as u8 + (j as i8 - y as i8).abs() as u8;
+                let (x, y) = positions[state[i][j] as usize];
+                distance += (i.abs_diff(x) + j.abs_diff(y)) as u8;
             }
         }
     }
@@ -22,31 +36,24 @@ fn manhattan_distance(state: &[[u8; 3]; 3]) -> u8 {
 }
 
 // Define the Linear Conflict distance heuristic function
-fn linear_conflict(state: &[[u8; 3]; 3]) -> u8 {
+fn linear_conflict(state: &[[u8; 3]; 3], goal: &[[u8; 3]; 3]) -> u8 {
+    let positions = goal_positions(goal);
     let mut count = 0;
     for i in 0..3 {
-        count += linear_conflict_row(&state[i]);
-        count += linear_conflict_column(&[state[0][i], state[1][i], state[2][i]]);
+        // Goal columns of the tiles of row i that belong in row i, and the same for column i
+        let row: Vec<usize> = (0..3)
+            .map(|j| state[i][j] as usize)
+            .filter(|&tile| tile != 0 && positions[tile].0 == i)
+            .map(|tile| positions[tile].1)
+            .collect();
+        let column: Vec<usize> = (0..3)
+            .map(|j| state[j][i] as usize)
+            .filter(|&tile| tile != 0 && positions[tile].1 == i)
+            .map(|tile| positions[tile].0)
+            .collect();
+        count += linear_conflict_penalty(&row) + linear_conflict_penalty(&column);
     }
-    count
-}
-
-fn linear_conflict_row(row: &[u8; 3]) -> u8 {
-    let mut count = 0;
-    for i in 0..3 {
-        if row[i] != 0 && row[i] / 3 == i as u8 {
-            for j in (i + 1)..2 {
-                if row[j] != 0 && row[j] / 3 == i as u8 && row[i] > row[j] {
-                    count += 2;
-                }
-            }
-        }
-    }
-    count
-}
-
-fn linear_conflict_column(column: &[u8; 3]) -> u8 {
-    linear_conflict_row(&[column[0], column[1], column[2]])
+    count as u8
 }
 
 // Define the State struct to represent a state in the search
@@ -60,9 +67,9 @@ struct State {
 }
 
 impl State {
-    fn new(state: [[u8; 3]; 3], cost: u8, parent: Option<Box<State>>) -> Self {
-        let manhattan = manhattan_distance(&state);
-        let linear_conflict = linear_conflict(&state);
+    fn new(state: [[u8; 3]; 3], cost: u8, parent: Option<Box<State>>, goal: &[[u8; 3]; 3]) -> Self {
+        let manhattan = manhattan_distance(&state, goal);
+        let linear_conflict = linear_conflict(&state, goal);
         Self {
             state,
             cost,
@@ -72,11 +79,11 @@ impl State {
         }
     }
 
-    fn is_goal_state(&self) -> bool {
-        self.state == GOAL_STATE
+    fn is_goal_state(&self, goal: &[[u8; 3]; 3]) -> bool {
+        self.state == *goal
     }
 
-    fn successors(&self) -> Vec<Self> {
+    fn successors(&self, goal: &[[u8; 3]; 3]) -> Vec<Self> {
         let mut successors = vec![];
         let mut x = 0;
         let mut y = 0;
@@ -95,41 +102,29 @@ impl State {
                 let mut new_state = self.state.clone();
                 new_state[x][y] = new_state[nx][ny];
                 new_state[nx][ny] = 0;
-                successors.push(Self::new(new_state, self.cost + 1, Some(Box::new(self.clone()))));
+                successors.push(Self::new(new_state, self.cost + 1, Some(Box::new(self.clone())), goal));
             }
         }
         successors
     }
 
     fn total_cost(&self) -> u8 {
-        self.cost + self.manhattan + 2*self.linear_conflict
-    }
-}
-
-// Implement the Ord and PartialOrd traits for the State struct, to enable ordering in BinaryHeap
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.total_cost().cmp(&self.total_cost())
-    }
-}
-
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+        self.cost + self.manhattan + self.linear_conflict
     }
 }
 
 // Define the A* search function
-pub fn a_star_search(start_state: [[u8; 3]; 3]) -> Option<Vec<[[u8; 3]; 3]>> {
-    // Define the priority queue to
-    // store the states to be expanded, with the starting state as the first element
-    let mut queue = BinaryHeap::new();
-    queue.push(State::new(start_state, 0, None));
+pub fn a_star_search(start_state: [[u8; 3]; 3], goal: [[u8; 3]; 3]) -> Option<Vec<[[u8; 3]; 3]>> {
+    // Define the open list to store the states to be expanded, with the starting state
+    // as the first element, one bucket per total cost and the deepest state first on ties
+    let mut queue = BucketOpenList::new(TieBreaking::LargerG);
+    let start = State::new(start_state, 0, None, &goal);
+    queue.push(start.total_cost() as u32, 0, start);
     // Define the set to store the visited states
     let mut visited = HashSet::new();
-    while let Some(current_state) = queue.pop() {
+    while let Some((_, _, current_state)) = queue.pop() {
         // Check if the current state is the goal state
-        if current_state.is_goal_state() {
+        if current_state.is_goal_state(&goal) {
             // Trace back the path from the goal state to the start state
             let mut path = vec![];
             let mut current = &current_state;
@@ -146,10 +141,10 @@ pub fn a_star_search(start_state: [[u8; 3]; 3]) -> Option<Vec<[[u8; 3]; 3]>> {
         visited.insert(current_state.clone());
     
         // Generate the successor states and add them to the priority queue
-        for successor_state in current_state.successors() {
+        for successor_state in current_state.successors(&goal) {
             // Check if the successor state has already been visited
             if !visited.contains(&successor_state) {
-                queue.push(successor_state);
+                queue.push(successor_state.total_cost() as u32, successor_state.cost as u32, successor_state);
             }
         }
     }
@@ -157,40 +152,14 @@ pub fn a_star_search(start_state: [[u8; 3]; 3]) -> Option<Vec<[[u8; 3]; 3]>> {
     None
 }
 
-fn get_invetrsion_count(linear_state: [u8; 9]) -> u16{
-    let mut invetrsion_count = 0;
-    for i in 0..9 {
-        for j in (i+1)..9 {
-            if linear_state[i] > 0 && linear_state[j] > 0 && linear_state[i] > linear_state[j] {
-                invetrsion_count += 1;
-            }
-        }
-    }
-    return invetrsion_count;
-}
-
-fn is_solvable(state: [[u8; 3]; 3]) -> bool{
-    let mut linear_state: [u8; 9] = [0; 9];
-    let mut counter = 0;
-    for row in state {
-        for val in row {
-            linear_state[counter] = val;
-            counter += 1;
-        }
-    }
-
-    let inversion_count = get_invetrsion_count(linear_state);
-    return inversion_count % 2 == 0;
-}
-
 //Shuffle state with Fisher–Yates shuffle
-pub fn random_state() -> [[u8; 3]; 3]{
+pub fn random_state(goal: [[u8; 3]; 3]) -> [[u8; 3]; 3]{
     let mut state:[[u8; 3]; 3];
     loop {
         let mut list: Vec<u8> = (0..=8).collect();
         list.shuffle(&mut Pcg64::from_entropy());
         state = [list[0..3].try_into().expect("Something went Wrong!"), list[3..6].try_into().expect("Something went Wrong!"), list[6..9].try_into().expect("Something went Wrong!")];
-        if is_solvable(state) {
+        if is_solvable_towards(&Board::from(state), &Board::from(goal)) {
             break;
         }
     }
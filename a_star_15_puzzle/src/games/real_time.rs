@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use crate::games::heuristics::manhattan_distance_to;
+use crate::games::sliding::{Board, Move};
+
+// Define the real-time search variants
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Variant {
+    // LRTA* stores the best lookahead value, which stays admissible across trials
+    Lrta,
+    // RTA* stores the second best value, which is better within a trial but may overestimate
+    Rta,
+}
+
+// Define the result of a single trial
+#[derive(Clone, Debug)]
+pub struct Trial {
+    pub moves: Vec<Move>,
+    pub solved: bool,
+    pub learned_states: usize,
+}
+
+// Define an agent that commits to one move at a time and learns heuristic values
+pub struct RealTimeAgent {
+    variant: Variant,
+    lookahead: u32,
+    goal: Board,
+    learned: HashMap<Board, u32>,
+}
+
+impl RealTimeAgent {
+    pub fn new(variant: Variant, lookahead: u32, goal: Board) -> Self {
+        Self {
+            variant,
+            lookahead: lookahead.max(1),
+            goal,
+            learned: HashMap::new(),
+        }
+    }
+
+    fn heuristic(&self, board: &Board) -> u32 {
+        match self.learned.get(board) {
+            Some(&h) => h,
+            None => manhattan_distance_to(board, &self.goal),
+        }
+    }
+
+    // Minimin lookahead: the cheapest g + h over the frontier at the given depth,
+    // never below the value already learned for an interior board
+    fn minimin(&self, board: &Board, depth: u32, last: Move) -> u32 {
+        let h = self.heuristic(board);
+        if depth == 0 || *board == self.goal {
+            return h;
+        }
+        let mut best = u32::MAX;
+        for (m, successor) in board.successors() {
+            if m == last.opposite() {
+                continue;
+            }
+            best = best.min(1 + self.minimin(&successor, depth - 1, m));
+        }
+        best.max(h)
+    }
+
+    // Choose the next move from the board and update the stored value of the board
+    pub fn step(&mut self, board: &Board) -> Move {
+        let mut best: Option<(u32, Move)> = None;
+        let mut second = u32::MAX;
+        for (m, successor) in board.successors() {
+            let value = 1 + self.minimin(&successor, self.lookahead - 1, m);
+            match best {
+                Some((b, _)) if value >= b => second = second.min(value),
+                _ => {
+                    if let Some((b, _)) = best {
+                        second = b;
+                    }
+                    best = Some((value, m));
+                }
+            }
+        }
+        let (best_value, best_move) = best.expect("every board has a successor");
+        let update = match self.variant {
+            Variant::Lrta => best_value,
+            Variant::Rta if second == u32::MAX => best_value,
+            Variant::Rta => second,
+        };
+        let current = self.heuristic(board);
+        self.learned.insert(board.clone(), current.max(update));
+        best_move
+    }
+
+    // Run one trial from the start, giving up after max_moves
+    pub fn trial(&mut self, start: &Board, max_moves: usize) -> Trial {
+        let mut board = start.clone();
+        let mut moves = vec![];
+        while board != self.goal && moves.len() < max_moves {
+            let m = self.step(&board);
+            board.apply(m);
+            moves.push(m);
+        }
+        Trial {
+            moves,
+            solved: board == self.goal,
+            learned_states: self.learned.len(),
+        }
+    }
+}
+
+// Run repeated trials from the same start, stopping early once the solution length
+// has stayed the same for `stable` trials in a row
+pub fn run_trials(agent: &mut RealTimeAgent, start: &Board, trials: usize, max_moves: usize, stable: usize) -> Vec<Trial> {
+    let mut results: Vec<Trial> = vec![];
+    let mut streak = 0;
+    for _ in 0..trials {
+        let trial = agent.trial(start, max_moves);
+        match results.last() {
+            Some(last) if last.solved && trial.solved && last.moves.len() == trial.moves.len() => streak += 1,
+            _ => streak = 1,
+        }
+        results.push(trial);
+        if stable > 0 && streak >= stable {
+            break;
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::test_support::{optimal_length, played, seeded_boards};
+
+    #[test]
+    fn trials_reach_the_goal() {
+        let goal = Board::goal(3, 3);
+        for variant in [Variant::Lrta, Variant::Rta] {
+            for start in seeded_boards(5) {
+                let mut agent = RealTimeAgent::new(variant, 2, goal.clone());
+                for trial in run_trials(&mut agent, &start, 5, 10_000, 0) {
+                    assert!(trial.solved);
+                    assert_eq!(played(&start, &trial.moves), Some(goal.clone()));
+                    assert!(trial.moves.len() as u32 >= optimal_length(&start));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lrta_learns_admissible_values() {
+        let goal = Board::goal(3, 3);
+        for start in seeded_boards(5) {
+            let mut agent = RealTimeAgent::new(Variant::Lrta, 3, goal.clone());
+            run_trials(&mut agent, &start, 10, 10_000, 0);
+            assert!(agent.learned.iter().all(|(board, &h)| h <= optimal_length(board)));
+        }
+    }
+}
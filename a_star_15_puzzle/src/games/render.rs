@@ -0,0 +1,173 @@
+use std::io;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use dialoguer::console::{style, Key, Term};
+
+use crate::games::sliding::{Board, Move};
+
+const START_DELAY: Duration = Duration::from_millis(400);
+const MIN_DELAY: Duration = Duration::from_millis(25);
+const MAX_DELAY: Duration = Duration::from_secs(3);
+
+// Draw a board with box-drawing characters, the blank left empty and `highlight` in colour
+pub fn render(board: &Board, highlight: Option<u16>) -> String {
+    let cols = board.cols();
+    let width = (board.tiles().len() - 1).to_string().len() + 2;
+    let border = |left: &str, middle: &str, right: &str| format!("{}{}{}", left, vec!["─".repeat(width); cols].join(middle), right);
+    let mut lines = vec![border("┌", "┬", "┐")];
+    for (row, tiles) in board.tiles().chunks(cols).enumerate() {
+        if row > 0 {
+            lines.push(border("├", "┼", "┤"));
+        }
+        let cells: Vec<String> = tiles
+            .iter()
+            .map(|&tile| {
+                let text = if tile == 0 { " ".repeat(width) } else { format!("{:^width$}", tile, width = width) };
+                if Some(tile) == highlight && tile != 0 {
+                    style(text).bold().cyan().to_string()
+                } else {
+                    text
+                }
+            })
+            .collect();
+        lines.push(format!("│{}│", cells.join("│")));
+    }
+    lines.push(border("└", "┴", "┘"));
+    lines.join("\n")
+}
+
+// Define the boards passed through when playing `moves` from `start`
+pub fn frames(start: &Board, moves: &[Move]) -> Vec<Board> {
+    let mut board = start.clone();
+    let mut frames = vec![board.clone()];
+    for &m in moves {
+        board.apply(m);
+        frames.push(board.clone());
+    }
+    frames
+}
+
+// The tile that moved between two consecutive boards: the one now on the old cell of the blank
+fn moved_tile(previous: &Board, board: &Board) -> Option<u16> {
+    let (row, col) = previous.blank();
+    Some(board.tiles()[row * board.cols() + col]).filter(|&tile| tile != 0)
+}
+
+fn frame_text(frames: &[Board], step: usize, paused: bool, delay: Duration) -> String {
+    let highlight = step.checked_sub(1).and_then(|previous| moved_tile(&frames[previous], &frames[step]));
+    let state = if paused { "paused" } else { "playing" };
+    format!(
+        "{}\nstep {}/{}, {}, {} ms per move\n[space] pause  [←/→] step  [+/-] speed  [q] quit",
+        render(&frames[step], highlight),
+        step,
+        frames.len() - 1,
+        state,
+        delay.as_millis()
+    )
+}
+
+// Play the boards one after another in place on the terminal, moved tiles highlighted. Space
+// pauses and resumes, the arrow keys step back and forth, + and - change the speed and q
+// leaves; at the last board the playback waits for a key. Without a terminal every board is
+// printed once.
+pub fn play(term: &Term, frames: &[Board]) -> io::Result<()> {
+    if frames.is_empty() {
+        return Ok(());
+    }
+    if !term.is_term() {
+        for step in 0..frames.len() {
+            let highlight = step.checked_sub(1).and_then(|previous| moved_tile(&frames[previous], &frames[step]));
+            term.write_line(&render(&frames[step], highlight))?;
+        }
+        return Ok(());
+    }
+    // Keys are read on their own thread so the playback can wait for a key or the next frame;
+    // the thread stops after the key that ends the playback
+    let (sender, keys) = mpsc::channel();
+    let reader = term.clone();
+    thread::spawn(move || {
+        while let Ok(key) = reader.read_key() {
+            let quit = matches!(key, Key::Char('q') | Key::Escape);
+            if sender.send(key).is_err() || quit {
+                break;
+            }
+        }
+    });
+    term.hide_cursor()?;
+    let last = frames.len() - 1;
+    let (mut step, mut paused, mut delay) = (0, false, START_DELAY);
+    let mut drawn = 0;
+    loop {
+        term.clear_last_lines(drawn)?;
+        let text = frame_text(frames, step, paused, delay);
+        drawn = text.lines().count();
+        term.write_line(&text)?;
+        let key = if paused || step == last {
+            keys.recv().ok()
+        } else {
+            match keys.recv_timeout(delay) {
+                Ok(key) => Some(key),
+                Err(RecvTimeoutError::Timeout) => {
+                    step += 1;
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => None,
+            }
+        };
+        match key {
+            None | Some(Key::Char('q')) | Some(Key::Escape) => break,
+            Some(Key::Char(' ')) => paused = !paused,
+            Some(Key::ArrowRight) => {
+                paused = true;
+                step = (step + 1).min(last);
+            }
+            Some(Key::ArrowLeft) => {
+                paused = true;
+                step = step.saturating_sub(1);
+            }
+            Some(Key::Char('+')) => delay = (delay / 2).max(MIN_DELAY),
+            Some(Key::Char('-')) => delay = (delay * 2).min(MAX_DELAY),
+            _ => {}
+        }
+    }
+    term.show_cursor()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::heuristics::manhattan_distance;
+    use crate::games::search;
+    use crate::games::test_support::{optimal_length, played, seeded_boards};
+
+    #[test]
+    fn frames_follow_the_moves() {
+        let goal = Board::goal(3, 3);
+        for board in seeded_boards(10) {
+            let moves = search::a_star_search(&board, &goal, &manhattan_distance).unwrap().moves;
+            let frames = frames(&board, &moves);
+            assert_eq!(frames.len(), moves.len() + 1);
+            assert_eq!(frames[0], board);
+            assert_eq!(frames.last(), played(&board, &moves).as_ref());
+            assert_eq!(frames.last(), Some(&goal));
+            assert_eq!(frames.len() as u32, optimal_length(&board) + 1);
+            for pair in frames.windows(2) {
+                let (row, col) = pair[0].blank();
+                assert_eq!(moved_tile(&pair[0], &pair[1]), Some(pair[1].tiles()[row * 3 + col]));
+            }
+        }
+    }
+
+    #[test]
+    fn boards_are_drawn_in_a_grid() {
+        let text = render(&Board::goal(4, 4), None);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2 * 4 + 1);
+        assert_eq!(lines[0], "┌────┬────┬────┬────┐");
+        assert_eq!(lines[1], "│ 1  │ 2  │ 3  │ 4  │");
+        assert_eq!(lines[7], "│ 13 │ 14 │ 15 │    │");
+        assert!(lines.iter().all(|line| line.chars().count() == 21));
+    }
+}
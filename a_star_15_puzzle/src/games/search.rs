@@ -0,0 +1,552 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::games::cost::CostModel;
+use crate::games::open_list::{BucketOpenList, TieBreaking};
+use crate::games::sliding::{is_solvable_towards, Board, Move, MOVES};
+
+// Define the counters reported by every search
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SearchStats {
+    pub expanded: usize,
+    pub generated: usize,
+    // Boards expanded again after a cheaper path to them was found
+    pub reopened: usize,
+    // Edges whose heuristic values, as the heuristic gives them before any pathmax, differ by
+    // more than the edge cost. Counted when detection is on, on every edge out of every
+    // expanded board, so an edge is counted again when its board is expanded again.
+    pub inconsistent_edges: usize,
+    // Heuristic values raised by bidirectional pathmax
+    pub pathmax_updates: usize,
+}
+
+// Define the switches for searching with inconsistent heuristics
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SearchOptions {
+    // Propagate heuristic values between parents and children (BPMX)
+    pub bpmx: bool,
+    // Count the edges on which the heuristic is inconsistent
+    pub detect_inconsistency: bool,
+    // Order of the A* open list among boards with the same f-value
+    pub tie_breaking: TieBreaking,
+}
+
+// Define the events a search reports while it runs, every one a no-op by default. A* and IDA*
+// call them on every board expanded or generated, on every child dropped as a duplicate (a
+// board already reached as cheaply for A*, the undo of the previous move for IDA*), at the
+// start of every IDA* iteration, every few million IDA* expansions and on the solution, and
+// IDA* hands out its state before every expansion so a run can be saved and resumed. Once
+// `should_stop` returns true the search gives up and returns None; it is asked before every A*
+// expansion and before every IDA* descent, and must keep returning true after that.
+pub trait SearchObserver {
+    fn on_expand(&mut self, _board: &Board, _g: u32, _h: u32) {}
+
+    fn on_generate(&mut self, _board: &Board, _g: u32, _h: u32) {}
+
+    fn on_duplicate(&mut self, _board: &Board, _g: u32) {}
+
+    fn on_iteration(&mut self, _bound: u32, _stats: &SearchStats) {}
+
+    fn on_progress(&mut self, _bound: u32, _stats: &SearchStats) {}
+
+    fn on_solution(&mut self, _moves: &[Move], _stats: &SearchStats) {}
+
+    // Called by IDA* before every expansion with the state it can be resumed from
+    fn on_snapshot(&mut self, _state: &IdaState) {}
+
+    fn should_stop(&mut self) -> bool {
+        false
+    }
+}
+
+// Define the observer of searches nobody watches
+pub struct NoObserver;
+
+impl SearchObserver for NoObserver {}
+
+// Define an observer passing the IDA* iterations and progress reports to a callback
+struct ProgressCallback<'a>(&'a mut dyn FnMut(u32, &SearchStats));
+
+impl SearchObserver for ProgressCallback<'_> {
+    fn on_iteration(&mut self, bound: u32, stats: &SearchStats) {
+        (self.0)(bound, stats);
+    }
+
+    fn on_progress(&mut self, bound: u32, stats: &SearchStats) {
+        (self.0)(bound, stats);
+    }
+}
+
+// Check the consistency of the heuristic across one edge, in both directions
+fn is_inconsistent(h: u32, child_h: u32, cost: u32) -> bool {
+    h.abs_diff(child_h) > cost
+}
+
+// Define a solution as the blank moves from the start together with the search counters
+#[derive(Clone, Debug)]
+pub struct Solution {
+    pub moves: Vec<Move>,
+    pub stats: SearchStats,
+}
+
+// Define a search node stored in an arena, pointing at its parent by index
+struct Node {
+    board: Board,
+    g: u32,
+    parent: Option<(usize, Move)>,
+}
+
+fn trace_moves(nodes: &[Node], mut index: usize) -> Vec<Move> {
+    let mut moves = vec![];
+    while let Some((parent, m)) = nodes[index].parent {
+        moves.push(m);
+        index = parent;
+    }
+    moves.reverse();
+    moves
+}
+
+// Define the A* search function over boards of any size, used as the optimal baseline.
+// The heuristic must estimate the distance to `goal`.
+pub fn a_star_search(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32) -> Option<Solution> {
+    a_star_search_with_costs(start, goal, heuristic, &CostModel::Unit)
+}
+
+// Define A* under any cost model, the heuristic must estimate the cost to `goal` under that model
+pub fn a_star_search_with_costs(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32, costs: &CostModel) -> Option<Solution> {
+    a_star_search_with_options(start, goal, heuristic, costs, &SearchOptions::default())
+}
+
+// Define A* that stays optimal with admissible but inconsistent heuristics: a board is expanded
+// again whenever a cheaper path to it turns up, and with BPMX the heuristic values learned
+// from neighbours are kept for later
+pub fn a_star_search_with_options(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32, costs: &CostModel, options: &SearchOptions) -> Option<Solution> {
+    a_star_search_observed(start, goal, heuristic, costs, options, &mut NoObserver)
+}
+
+// Define A* with options that reports its events to `observer`
+pub fn a_star_search_observed(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32, costs: &CostModel, options: &SearchOptions, observer: &mut dyn SearchObserver) -> Option<Solution> {
+    let mut stats = SearchStats::default();
+    let mut nodes = vec![Node { board: start.clone(), g: 0, parent: None }];
+    let mut queue = BucketOpenList::new(options.tie_breaking);
+    queue.push(heuristic(start), 0, 0);
+    let mut best_g: HashMap<Board, u32> = HashMap::new();
+    best_g.insert(start.clone(), 0);
+    let mut learned_h: HashMap<Board, u32> = HashMap::new();
+    let mut closed = HashSet::new();
+    while let Some((f, g, index)) = queue.pop() {
+        if observer.should_stop() {
+            return None;
+        }
+        // Skip entries left behind by a cheaper path to the same board
+        if best_g[&nodes[index].board] < g {
+            continue;
+        }
+        if nodes[index].board == *goal {
+            let moves = trace_moves(&nodes, index);
+            observer.on_solution(&moves, &stats);
+            return Some(Solution { moves, stats });
+        }
+        if !closed.insert(nodes[index].board.clone()) {
+            stats.reopened += 1;
+        }
+        stats.expanded += 1;
+        // The value may have been raised by BPMX after the board was queued
+        let mut h = (f - g).max(learned_h.get(&nodes[index].board).copied().unwrap_or(0));
+        observer.on_expand(&nodes[index].board, g, h);
+        // Detection compares the values of the heuristic itself, not the ones raised by BPMX
+        let raw_h = if options.detect_inconsistency { heuristic(&nodes[index].board) } else { h };
+        let mut children = vec![];
+        for (m, successor) in nodes[index].board.successors() {
+            let cost = costs.move_cost(&nodes[index].board, m);
+            let mut child_h = heuristic(&successor);
+            if options.detect_inconsistency && is_inconsistent(raw_h, child_h, cost) {
+                stats.inconsistent_edges += 1;
+            }
+            if let Some(&learned) = learned_h.get(&successor) {
+                child_h = child_h.max(learned);
+            }
+            if options.bpmx && child_h.saturating_sub(cost) > h {
+                h = child_h - cost;
+                stats.pathmax_updates += 1;
+            }
+            children.push((m, successor, cost, child_h));
+        }
+        if options.bpmx {
+            learned_h.insert(nodes[index].board.clone(), h);
+        }
+        for (m, successor, cost, mut child_h) in children {
+            if options.bpmx && h.saturating_sub(cost) > child_h {
+                child_h = h - cost;
+                stats.pathmax_updates += 1;
+                learned_h.insert(successor.clone(), child_h);
+            }
+            let g = g + cost;
+            if best_g.get(&successor).is_some_and(|&old| old <= g) {
+                observer.on_duplicate(&successor, g);
+                continue;
+            }
+            stats.generated += 1;
+            observer.on_generate(&successor, g, child_h);
+            best_g.insert(successor.clone(), g);
+            queue.push(g + child_h, g, nodes.len());
+            nodes.push(Node { board: successor, g, parent: Some((index, m)) });
+        }
+    }
+    None
+}
+
+// Define greedy best-first search, which always expands the board with the smallest h
+pub fn greedy_best_first_search(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32) -> Option<Solution> {
+    let mut stats = SearchStats::default();
+    let mut nodes = vec![Node { board: start.clone(), g: 0, parent: None }];
+    let mut queue = BinaryHeap::new();
+    queue.push(Reverse((heuristic(start), 0)));
+    let mut visited = HashSet::new();
+    visited.insert(start.clone());
+    while let Some(Reverse((_, index))) = queue.pop() {
+        if nodes[index].board == *goal {
+            return Some(Solution { moves: trace_moves(&nodes, index), stats });
+        }
+        stats.expanded += 1;
+        let g = nodes[index].g + 1;
+        for (m, successor) in nodes[index].board.successors() {
+            if visited.insert(successor.clone()) {
+                stats.generated += 1;
+                queue.push(Reverse((heuristic(&successor), nodes.len())));
+                nodes.push(Node { board: successor, g, parent: Some((index, m)) });
+            }
+        }
+    }
+    None
+}
+
+// Define beam search: each layer keeps only the `width` boards with the smallest h,
+// so memory grows with width times depth instead of with the state space
+pub fn beam_search(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32, width: usize, max_depth: usize) -> Option<Solution> {
+    let mut stats = SearchStats::default();
+    let mut nodes = vec![Node { board: start.clone(), g: 0, parent: None }];
+    let mut visited = HashSet::new();
+    visited.insert(start.clone());
+    let mut layer = vec![0];
+    for _ in 0..max_depth {
+        let mut candidates: Vec<(u32, usize)> = vec![];
+        for &index in &layer {
+            if nodes[index].board == *goal {
+                return Some(Solution { moves: trace_moves(&nodes, index), stats });
+            }
+            stats.expanded += 1;
+            let g = nodes[index].g + 1;
+            for (m, successor) in nodes[index].board.successors() {
+                if visited.insert(successor.clone()) {
+                    stats.generated += 1;
+                    candidates.push((heuristic(&successor), nodes.len()));
+                    nodes.push(Node { board: successor, g, parent: Some((index, m)) });
+                }
+            }
+        }
+        if candidates.is_empty() {
+            return None;
+        }
+        candidates.sort_unstable();
+        candidates.truncate(width.max(1));
+        layer = candidates.into_iter().map(|(_, index)| index).collect();
+    }
+    layer
+        .into_iter()
+        .find(|&index| nodes[index].board == *goal)
+        .map(|index| Solution { moves: trace_moves(&nodes, index), stats })
+}
+
+// Number of expansions between two progress reports of a long IDA* iteration
+const PROGRESS_INTERVAL: usize = 1 << 22;
+
+// Define the IDA* search function over boards of any size, it works in place on a single board
+pub fn ida_star_search(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32) -> Option<Solution> {
+    ida_star_search_with_progress(start, goal, heuristic, &mut |_, _| {})
+}
+
+// Define IDA* that reports the current bound and counters at the start of every iteration
+// and every few million expansions, for searches that run for minutes or hours
+pub fn ida_star_search_with_progress(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32, progress: &mut dyn FnMut(u32, &SearchStats)) -> Option<Solution> {
+    ida_star_search_with_costs(start, goal, heuristic, &CostModel::Unit, progress)
+}
+
+// Define IDA* under any cost model. The bound always grows to the smallest f-value that
+// exceeded it, so with non-unit costs it may take more, smaller steps but stays optimal.
+pub fn ida_star_search_with_costs(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32, costs: &CostModel, progress: &mut dyn FnMut(u32, &SearchStats)) -> Option<Solution> {
+    ida_star_search_with_options(start, goal, heuristic, costs, &SearchOptions::default(), progress)
+}
+
+// Define IDA* with optional BPMX: before descending, the children's values raise the parent's
+// (h(n) >= h(c) - cost) and the parent's raise the children's (h(c) >= h(n) - cost), and a
+// value learned below a child can still cut off its remaining siblings
+pub fn ida_star_search_with_options(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32, costs: &CostModel, options: &SearchOptions, progress: &mut dyn FnMut(u32, &SearchStats)) -> Option<Solution> {
+    ida_star_search_observed(start, goal, heuristic, costs, options, &mut ProgressCallback(progress))
+}
+
+// Define IDA* with options that reports its events to `observer`
+pub fn ida_star_search_observed(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32, costs: &CostModel, options: &SearchOptions, observer: &mut dyn SearchObserver) -> Option<Solution> {
+    // Define the initial bound as the heuristic value of the starting state
+    let state = IdaState { bound: heuristic(start), ..IdaState::default() };
+    resume_ida_star_search(start, goal, heuristic, costs, options, state, observer)
+}
+
+// Define the state of an IDA* run before an expansion: the bound of the current iteration, the
+// smallest f-value above it met so far, the moves from the start to the board about to be
+// expanded and the counters. Every subtree left of the path has been searched, so a run
+// resumed from the state replays the path and carries on from the board at its end.
+#[derive(Clone, Debug, Default)]
+pub struct IdaState {
+    pub bound: u32,
+    pub next_bound: Option<u32>,
+    pub path: Vec<Move>,
+    pub stats: SearchStats,
+}
+
+// Define IDA* carrying on from a state seen by `on_snapshot`, the counters carrying on from the
+// saved ones. The search must have the same boards, heuristic, costs and options as the one
+// that was saved. Heuristic values raised by BPMX left of the path are lost, so a resumed run
+// with BPMX may search a few more boards than an uninterrupted one.
+pub fn resume_ida_star_search(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32, costs: &CostModel, options: &SearchOptions, state: IdaState, observer: &mut dyn SearchObserver) -> Option<Solution> {
+    if !is_solvable_towards(start, goal) {
+        return None;
+    }
+    let h = heuristic(start);
+    let mut ida = Ida { goal, heuristic, costs, options, state, observer };
+    loop {
+        ida.observer.on_iteration(ida.state.bound, &ida.state.stats);
+        let resume = std::mem::take(&mut ida.state.path);
+        let (t, _) = ida.search(&mut start.clone(), 0, h, &resume);
+        if t == 0 {
+            let IdaState { path: moves, stats, .. } = ida.state;
+            ida.observer.on_solution(&moves, &stats);
+            return Some(Solution { moves, stats });
+        }
+        if ida.observer.should_stop() {
+            return None;
+        }
+        // The subtrees searched before a resume count towards the next bound as well
+        ida.state.bound = ida.state.next_bound.take()?;
+    }
+}
+
+// Define an IDA* run: what stays fixed, the state and the observer
+struct Ida<'a> {
+    goal: &'a Board,
+    heuristic: &'a dyn Fn(&Board) -> u32,
+    costs: &'a CostModel,
+    options: &'a SearchOptions,
+    state: IdaState,
+    observer: &'a mut dyn SearchObserver,
+}
+
+impl Ida<'_> {
+    // Note an f-value above the bound and return it
+    fn cut_off(&mut self, f: u32) -> u32 {
+        self.state.next_bound = Some(self.state.next_bound.map_or(f, |next| next.min(f)));
+        f
+    }
+
+    // Depth-first search below the bound from a board with heuristic value h, the path being
+    // the moves from the start to the board. Returns 0 when the goal was found and otherwise
+    // the smallest f-value that exceeded the bound, together with the heuristic value of the
+    // board after pathmax. Moves left in `resume` lead back to where a saved run stopped: the
+    // boards on the way are not counted or reported again and the children left of the path
+    // are skipped.
+    fn search(&mut self, board: &mut Board, g: u32, mut h: u32, resume: &[Move]) -> (u32, u32) {
+        let Ida { goal, heuristic, costs, options, .. } = *self;
+        let bound = self.state.bound;
+        let f = g + h;
+        if f > bound {
+            return (self.cut_off(f), h);
+        }
+        if board == goal {
+            return (0, h);
+        }
+        let counting = resume.is_empty();
+        if counting {
+            // Announce the state before counting the expansion, a resumed run expands this board
+            self.observer.on_snapshot(&self.state);
+            self.state.stats.expanded += 1;
+            self.observer.on_expand(board, g, h);
+            if self.state.stats.expanded.is_multiple_of(PROGRESS_INTERVAL) {
+                self.observer.on_progress(bound, &self.state.stats);
+            }
+        }
+        // Detection compares the values of the heuristic itself, not the ones raised by BPMX
+        let raw_h = if options.detect_inconsistency { heuristic(board) } else { h };
+        let detect = counting && options.detect_inconsistency;
+        // Evaluate every child first, so BPMX can look at all of them before descending
+        let mut children = [None; 4];
+        for (child, m) in children.iter_mut().zip(MOVES) {
+            if self.state.path.last() == Some(&m.opposite()) {
+                let cost = costs.move_cost(board, m);
+                if counting && board.apply(m) {
+                    self.observer.on_duplicate(board, g + cost);
+                    if detect && is_inconsistent(raw_h, heuristic(board), cost) {
+                        self.state.stats.inconsistent_edges += 1;
+                    }
+                    board.apply(m.opposite());
+                }
+                continue;
+            }
+            let cost = costs.move_cost(board, m);
+            if !board.apply(m) {
+                continue;
+            }
+            let child_h = heuristic(board);
+            board.apply(m.opposite());
+            if detect && is_inconsistent(raw_h, child_h, cost) {
+                self.state.stats.inconsistent_edges += 1;
+            }
+            if options.bpmx && child_h.saturating_sub(cost) > h {
+                h = child_h - cost;
+                if counting {
+                    self.state.stats.pathmax_updates += 1;
+                }
+            }
+            *child = Some((m, cost, child_h));
+        }
+        if g + h > bound {
+            return (self.cut_off(g + h), h);
+        }
+        // Children left of the resumed move were searched before the run was saved
+        let (first, rest) = match resume.split_first() {
+            Some((&next, rest)) => (MOVES.iter().position(|&m| m == next).expect("move is one of MOVES"), rest),
+            None => (0, resume),
+        };
+        let mut min_cost = u32::MAX;
+        for (i, child) in children.into_iter().enumerate().skip(first) {
+            let Some((m, cost, mut child_h)) = child else { continue };
+            let resumed = !counting && i == first;
+            if self.observer.should_stop() {
+                return (u32::MAX, h);
+            }
+            if options.bpmx && h.saturating_sub(cost) > child_h {
+                child_h = h - cost;
+                if !resumed {
+                    self.state.stats.pathmax_updates += 1;
+                }
+            }
+            board.apply(m);
+            if !resumed {
+                self.state.stats.generated += 1;
+                self.observer.on_generate(board, g + cost, child_h);
+            }
+            self.state.path.push(m);
+            let (t, child_h) = self.search(board, g + cost, child_h, if resumed { rest } else { &[] });
+            if t == 0 {
+                return (0, h);
+            }
+            min_cost = min_cost.min(t);
+            self.state.path.pop();
+            board.apply(m.opposite());
+            if options.bpmx && child_h.saturating_sub(cost) > h {
+                h = child_h - cost;
+                self.state.stats.pathmax_updates += 1;
+                if g + h > bound {
+                    return (min_cost.min(self.cut_off(g + h)), h);
+                }
+            }
+        }
+        (min_cost, h)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::heuristics::{manhattan_distance, weighted_manhattan_distance_to};
+    use crate::games::sliding;
+    use crate::games::test_support::{optimal_length, played, seeded_boards};
+
+    #[test]
+    fn greedy_and_beam_solutions_reach_the_goal() {
+        let goal = Board::goal(3, 3);
+        for start in seeded_boards(10) {
+            let greedy = greedy_best_first_search(&start, &goal, &manhattan_distance).unwrap();
+            assert_eq!(played(&start, &greedy.moves), Some(goal.clone()));
+            let beam = beam_search(&start, &goal, &manhattan_distance, 50, 1000).unwrap();
+            assert_eq!(played(&start, &beam.moves), Some(goal.clone()));
+            assert!(beam.moves.len() as u32 >= optimal_length(&start));
+        }
+    }
+
+    #[test]
+    fn beam_wider_than_every_layer_is_optimal() {
+        let goal = Board::goal(3, 3);
+        for start in seeded_boards(5) {
+            let beam = beam_search(&start, &goal, &manhattan_distance, usize::MAX, 40).unwrap();
+            assert_eq!(beam.moves.len() as u32, optimal_length(&start));
+        }
+    }
+
+    #[test]
+    fn weighted_searches_match_dijkstra() {
+        let goal = Board::goal(3, 3);
+        let costs = CostModel::TileValue;
+        let heuristic = |board: &Board| weighted_manhattan_distance_to(board, &goal, &costs);
+        for seed in 0..10 {
+            let start = sliding::seeded_n_random_moves_from_goal(3, 3, 20, seed);
+            let dijkstra = a_star_search_with_costs(&start, &goal, &|_| 0, &costs).unwrap();
+            let optimum = costs.path_cost(&start, &dijkstra.moves);
+            let a_star = a_star_search_with_costs(&start, &goal, &heuristic, &costs).unwrap();
+            assert_eq!(costs.path_cost(&start, &a_star.moves), optimum);
+            let ida_star = ida_star_search_with_costs(&start, &goal, &heuristic, &costs, &mut |_, _| {}).unwrap();
+            assert_eq!(costs.path_cost(&start, &ida_star.moves), optimum);
+            assert_eq!(played(&start, &ida_star.moves), Some(goal.clone()));
+        }
+    }
+
+    // Admissible but inconsistent: the Manhattan distance on half of the boards, zero elsewhere
+    fn patchy(board: &Board) -> u32 {
+        if board.tiles().iter().step_by(2).sum::<u16>() % 2 == 0 {
+            manhattan_distance(board)
+        } else {
+            0
+        }
+    }
+
+    struct Expanded(Vec<Board>);
+
+    impl SearchObserver for Expanded {
+        fn on_expand(&mut self, board: &Board, _g: u32, _h: u32) {
+            self.0.push(board.clone());
+        }
+    }
+
+    #[test]
+    fn searches_with_bpmx_stay_optimal() {
+        let goal = Board::goal(3, 3);
+        for bpmx in [false, true] {
+            let options = SearchOptions { bpmx, detect_inconsistency: true, ..SearchOptions::default() };
+            for start in seeded_boards(10) {
+                let a_star = a_star_search_with_options(&start, &goal, &patchy, &CostModel::Unit, &options).unwrap();
+                assert_eq!(a_star.moves.len() as u32, optimal_length(&start));
+                let ida_star = ida_star_search_with_options(&start, &goal, &patchy, &CostModel::Unit, &options, &mut |_, _| {}).unwrap();
+                assert_eq!(ida_star.moves.len() as u32, optimal_length(&start));
+                assert_eq!(played(&start, &ida_star.moves), Some(goal.clone()));
+            }
+        }
+    }
+
+    #[test]
+    fn inconsistent_edges_count_the_raw_heuristic() {
+        let goal = Board::goal(3, 3);
+        let inconsistent_edges = |boards: &[Board]| -> usize {
+            boards.iter().map(|board| board.successors().iter().filter(|(_, child)| is_inconsistent(patchy(board), patchy(child), 1)).count()).sum()
+        };
+        for bpmx in [false, true] {
+            let options = SearchOptions { bpmx, detect_inconsistency: true, ..SearchOptions::default() };
+            for start in seeded_boards(5) {
+                let mut expanded = Expanded(vec![]);
+                let a_star = a_star_search_observed(&start, &goal, &patchy, &CostModel::Unit, &options, &mut expanded).unwrap();
+                assert_eq!(a_star.stats.inconsistent_edges, inconsistent_edges(&expanded.0));
+                let mut expanded = Expanded(vec![]);
+                let ida_star = ida_star_search_observed(&start, &goal, &patchy, &CostModel::Unit, &options, &mut expanded).unwrap();
+                assert_eq!(ida_star.stats.inconsistent_edges, inconsistent_edges(&expanded.0));
+            }
+        }
+    }
+}
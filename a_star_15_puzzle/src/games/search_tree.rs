@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::games::cost::CostModel;
+use crate::games::search::{self, SearchObserver, SearchOptions, SearchStats};
+use crate::games::sliding::{Board, Move};
+
+// Define what happened to a node of a recorded search
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum NodeKind {
+    // Expanded, or selected as the goal
+    Expanded,
+    // Generated but still open when the search stopped
+    Open,
+    // Dropped because the board was already reached at least as cheaply, or for IDA* because
+    // it undoes the previous move
+    Duplicate,
+    // Generated by IDA* with an f-value above the bound of the iteration
+    Cutoff,
+}
+
+// Define one node of a recorded search: the board with its g and h values, the position in the
+// expansion order when it was expanded and the IDA* iteration it belongs to (0 for A*)
+#[derive(Clone, Debug)]
+pub struct TreeNode {
+    pub board: Board,
+    pub parent: Option<(usize, Move)>,
+    pub g: u32,
+    pub h: u32,
+    pub kind: NodeKind,
+    pub order: Option<usize>,
+    pub iteration: usize,
+}
+
+// Define the nodes a search generated, the nodes of the solution path from the start to the
+// goal, and the bounds of the IDA* iterations (empty for A*). A truncated search stopped at the
+// node cap, possibly before finding the goal.
+#[derive(Clone, Debug, Default)]
+pub struct SearchTree {
+    pub nodes: Vec<TreeNode>,
+    pub solution: Vec<usize>,
+    pub bounds: Vec<u32>,
+    pub truncated: bool,
+}
+
+impl SearchTree {
+    fn add(&mut self, node: TreeNode, cap: usize) -> Option<usize> {
+        if self.nodes.len() >= cap {
+            self.truncated = true;
+            return None;
+        }
+        self.nodes.push(node);
+        Some(self.nodes.len() - 1)
+    }
+
+    fn mark_solution(&mut self, mut index: usize) {
+        let mut path = vec![index];
+        while let Some((parent, _)) = self.nodes[index].parent {
+            path.push(parent);
+            index = parent;
+        }
+        path.reverse();
+        self.solution = path;
+    }
+
+    // Blank moves of the solution, empty when no solution was found
+    pub fn moves(&self) -> Vec<Move> {
+        self.solution.iter().filter_map(|&index| self.nodes[index].parent.map(|(_, m)| m)).collect()
+    }
+}
+
+// Define an observer that records the nodes of a unit-cost A* or IDA* search as a tree. A child
+// hangs below the node expanded last one level above it, which for IDA* is the current path and
+// for A* the board being expanded; an expansion belongs to the node generated last for its
+// board, which for A* is the cheapest one. Every event after the cap is hit is ignored and
+// stops the search.
+struct TreeRecorder<'a> {
+    start: &'a Board,
+    heuristic: &'a dyn Fn(&Board) -> u32,
+    cap: usize,
+    tree: SearchTree,
+    order: usize,
+    // Bound of the current IDA* iteration, None for A*
+    bound: Option<u32>,
+    latest: HashMap<Board, usize>,
+    // Node expanded last at every g-value
+    expanded: Vec<usize>,
+}
+
+impl<'a> TreeRecorder<'a> {
+    fn new(start: &'a Board, heuristic: &'a dyn Fn(&Board) -> u32, cap: usize) -> Self {
+        Self { start, heuristic, cap, tree: SearchTree::default(), order: 0, bound: None, latest: HashMap::new(), expanded: vec![] }
+    }
+
+    fn add_root(&mut self) -> Option<usize> {
+        let iteration = self.tree.bounds.len().saturating_sub(1);
+        let root = TreeNode { board: self.start.clone(), parent: None, g: 0, h: (self.heuristic)(self.start), kind: NodeKind::Open, order: None, iteration };
+        let root = self.tree.add(root, self.cap)?;
+        self.latest.insert(self.start.clone(), root);
+        Some(root)
+    }
+
+    fn add_child(&mut self, board: &Board, g: u32, h: u32, kind: NodeKind) -> Option<usize> {
+        let parent = self.expanded[g as usize - 1];
+        let parent_board = &self.tree.nodes[parent].board;
+        let m = parent_board.successors().into_iter().find(|(_, successor)| successor == board).map(|(m, _)| m).expect("child is a successor of its parent");
+        let iteration = self.tree.nodes[parent].iteration;
+        self.tree.add(TreeNode { board: board.clone(), parent: Some((parent, m)), g, h, kind, order: None, iteration }, self.cap)
+    }
+
+    fn mark_expanded(&mut self, index: usize) {
+        self.order += 1;
+        self.tree.nodes[index].kind = NodeKind::Expanded;
+        self.tree.nodes[index].order = Some(self.order);
+    }
+}
+
+impl SearchObserver for TreeRecorder<'_> {
+    fn on_iteration(&mut self, bound: u32, _stats: &SearchStats) {
+        if self.tree.truncated {
+            return;
+        }
+        self.tree.bounds.push(bound);
+        self.bound = Some(bound);
+        if self.add_root().is_none() {
+            self.tree.bounds.pop();
+        }
+    }
+
+    fn on_expand(&mut self, board: &Board, g: u32, _h: u32) {
+        if self.tree.truncated {
+            return;
+        }
+        let index = self.latest[board];
+        self.mark_expanded(index);
+        // A* may expand deeper than any board before, the levels skipped are never looked up
+        self.expanded.resize(g as usize + 1, index);
+        self.expanded[g as usize] = index;
+    }
+
+    fn on_generate(&mut self, board: &Board, g: u32, h: u32) {
+        if self.tree.truncated {
+            return;
+        }
+        let cutoff = self.bound.is_some_and(|bound| g + h > bound);
+        let kind = if cutoff { NodeKind::Cutoff } else { NodeKind::Open };
+        let Some(child) = self.add_child(board, g, h, kind) else { return };
+        // A board generated again is only reached more cheaply than before, which supersedes
+        // the earlier node unless that one was expanded already
+        if let Some(previous) = self.latest.insert(board.clone(), child) {
+            if self.tree.nodes[previous].kind == NodeKind::Open {
+                self.tree.nodes[previous].kind = NodeKind::Duplicate;
+            }
+        }
+    }
+
+    fn on_duplicate(&mut self, board: &Board, g: u32) {
+        if !self.tree.truncated {
+            self.add_child(board, g, (self.heuristic)(board), NodeKind::Duplicate);
+        }
+    }
+
+    // The goal is never expanded, so follow the moves from the root to find and mark it
+    fn on_solution(&mut self, moves: &[Move], _stats: &SearchStats) {
+        if self.tree.truncated {
+            return;
+        }
+        let mut index = self.tree.nodes.iter().rposition(|node| node.parent.is_none()).expect("the search has a root");
+        for &m in moves {
+            index = self.tree.nodes.iter().rposition(|node| node.parent == Some((index, m))).expect("solution path was recorded");
+        }
+        self.mark_expanded(index);
+        self.tree.mark_solution(index);
+    }
+
+    fn should_stop(&mut self) -> bool {
+        self.tree.truncated
+    }
+}
+
+// Record the nodes A* generates with unit costs, stopping after `cap` nodes. Every child is
+// recorded, including the ones dropped as duplicates, and an open node superseded by a cheaper
+// path to its board is marked as a duplicate too.
+pub fn a_star_tree(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32, cap: usize) -> SearchTree {
+    let mut recorder = TreeRecorder::new(start, heuristic, cap);
+    if recorder.add_root().is_some() {
+        search::a_star_search_observed(start, goal, heuristic, &CostModel::Unit, &SearchOptions::default(), &mut recorder);
+    }
+    recorder.tree
+}
+
+// Record the nodes IDA* generates with unit costs, every iteration as its own tree, stopping
+// after `cap` nodes over all iterations. Children undoing the previous move are recorded as
+// duplicates and children above the bound as cutoffs.
+pub fn ida_star_tree(start: &Board, goal: &Board, heuristic: &dyn Fn(&Board) -> u32, cap: usize) -> SearchTree {
+    let mut recorder = TreeRecorder::new(start, heuristic, cap);
+    search::ida_star_search_observed(start, goal, heuristic, &CostModel::Unit, &SearchOptions::default(), &mut recorder);
+    recorder.tree
+}
+
+fn label(node: &TreeNode) -> String {
+    let width = (node.board.tiles().len() - 1).to_string().len();
+    let rows: Vec<String> = node
+        .board
+        .tiles()
+        .chunks(node.board.cols())
+        .map(|row| {
+            let cells: Vec<String> = row.iter().map(|&tile| if tile == 0 { format!("{:>width$}", "_", width = width) } else { format!("{:>width$}", tile, width = width) }).collect();
+            cells.join(" ")
+        })
+        .collect();
+    let order = node.order.map_or(String::new(), |order| format!("\\n#{}", order));
+    format!("{}\\ng={} h={} f={}{}", rows.join("\\n"), node.g, node.h, node.g + node.h, order)
+}
+
+fn attributes(kind: NodeKind, on_path: bool) -> &'static str {
+    match (kind, on_path) {
+        (_, true) => "style=\"filled,bold\", fillcolor=\"#ffd7a8\", color=\"#d2691e\", penwidth=2",
+        (NodeKind::Expanded, _) => "style=filled, fillcolor=\"#dbe9f6\"",
+        (NodeKind::Open, _) => "style=solid",
+        (NodeKind::Duplicate, _) => "style=\"filled,dashed\", fillcolor=\"#eeeeee\", fontcolor=\"#888888\", color=\"#888888\"",
+        (NodeKind::Cutoff, _) => "style=dotted, fontcolor=\"#888888\"",
+    }
+}
+
+// Write a recorded search as a GraphViz digraph: boards with g, h, f and the expansion order,
+// the solution path in orange, duplicates greyed and dashed, cutoffs dotted. IDA* iterations
+// become clusters labelled with their bound. Render with e.g. `dot -Tsvg tree.dot -o tree.svg`.
+pub fn write_dot(writer: &mut dyn Write, tree: &SearchTree) -> io::Result<()> {
+    let mut on_path = vec![false; tree.nodes.len()];
+    for &index in &tree.solution {
+        on_path[index] = true;
+    }
+    writeln!(writer, "digraph search {{")?;
+    writeln!(writer, "    node [shape=box, fontname=\"monospace\"];")?;
+    writeln!(writer, "    edge [fontname=\"monospace\"];")?;
+    let write_node = |writer: &mut dyn Write, index: usize, indent: &str| {
+        let node = &tree.nodes[index];
+        writeln!(writer, "{}n{} [label=\"{}\", {}];", indent, index, label(node), attributes(node.kind, on_path[index]))
+    };
+    if tree.bounds.is_empty() {
+        for index in 0..tree.nodes.len() {
+            write_node(writer, index, "    ")?;
+        }
+    } else {
+        for (iteration, bound) in tree.bounds.iter().enumerate() {
+            writeln!(writer, "    subgraph cluster_{} {{", iteration)?;
+            writeln!(writer, "        label=\"iteration {}, bound {}\";", iteration + 1, bound)?;
+            for index in (0..tree.nodes.len()).filter(|&index| tree.nodes[index].iteration == iteration) {
+                write_node(writer, index, "        ")?;
+            }
+            writeln!(writer, "    }}")?;
+        }
+    }
+    for (index, node) in tree.nodes.iter().enumerate() {
+        if let Some((parent, m)) = node.parent {
+            let style = if on_path[index] { ", color=\"#d2691e\", penwidth=2" } else { "" };
+            writeln!(writer, "    n{} -> n{} [label=\"{}\"{}];", parent, index, m.to_char(), style)?;
+        }
+    }
+    if tree.truncated {
+        writeln!(writer, "    truncated [shape=note, label=\"truncated at {} nodes\"];", tree.nodes.len())?;
+    }
+    writeln!(writer, "}}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::heuristics::manhattan_distance;
+    use crate::games::test_support::{optimal_length, played, seeded_boards};
+
+    // Admissible but inconsistent: the Manhattan distance on half of the boards, zero elsewhere
+    fn patchy(board: &Board) -> u32 {
+        if board.tiles().iter().step_by(2).sum::<u16>() % 2 == 0 {
+            manhattan_distance(board)
+        } else {
+            0
+        }
+    }
+
+    // Check the solution and the expansion order, returning the number of boards expanded twice
+    fn assert_well_formed(tree: &SearchTree, start: &Board) -> usize {
+        assert!(!tree.truncated);
+        assert_eq!(tree.moves().len() as u32, optimal_length(start));
+        assert_eq!(played(start, &tree.moves()), Some(Board::goal(3, 3)));
+        let mut orders: Vec<usize> = tree.nodes.iter().filter_map(|node| node.order).collect();
+        orders.sort_unstable();
+        assert_eq!(orders, (1..=orders.len()).collect::<Vec<_>>());
+        let mut expanded = HashMap::new();
+        for node in &tree.nodes {
+            assert_eq!(node.order.is_some(), node.kind == NodeKind::Expanded);
+            if let Some((parent, m)) = node.parent {
+                let mut board = tree.nodes[parent].board.clone();
+                assert!(board.apply(m));
+                assert_eq!(board, node.board);
+                assert_eq!(node.g, tree.nodes[parent].g + 1);
+                assert_eq!(tree.nodes[parent].kind, NodeKind::Expanded);
+            }
+            if node.kind == NodeKind::Expanded {
+                *expanded.entry((node.iteration, node.board.clone())).or_insert(0) += 1;
+            }
+        }
+        expanded.values().filter(|&&count| count > 1).count()
+    }
+
+    #[test]
+    fn a_star_tree_matches_the_search() {
+        for board in seeded_boards(20) {
+            let tree = a_star_tree(&board, &Board::goal(3, 3), &manhattan_distance, usize::MAX);
+            assert_eq!(assert_well_formed(&tree, &board), 0);
+            let stats = search::a_star_search(&board, &Board::goal(3, 3), &manhattan_distance).unwrap().stats;
+            // The goal is marked as expanded too
+            assert_eq!(tree.nodes.iter().filter(|node| node.kind == NodeKind::Expanded).count(), stats.expanded + 1);
+            assert!(tree.bounds.is_empty());
+        }
+    }
+
+    #[test]
+    fn reopened_boards_stay_expanded() {
+        let mut reopened = 0;
+        for board in seeded_boards(20) {
+            let tree = a_star_tree(&board, &Board::goal(3, 3), &patchy, usize::MAX);
+            reopened += assert_well_formed(&tree, &board);
+        }
+        assert!(reopened > 0);
+    }
+
+    #[test]
+    fn ida_star_tree_matches_the_search() {
+        for board in seeded_boards(10) {
+            let tree = ida_star_tree(&board, &Board::goal(3, 3), &manhattan_distance, usize::MAX);
+            assert_well_formed(&tree, &board);
+            assert_eq!(tree.bounds.first(), Some(&manhattan_distance(&board)));
+            assert_eq!(tree.bounds.last(), Some(&optimal_length(&board)));
+            for (iteration, &bound) in tree.bounds.iter().enumerate() {
+                let nodes: Vec<&TreeNode> = tree.nodes.iter().filter(|node| node.iteration == iteration).collect();
+                assert_eq!(nodes.iter().filter(|node| node.parent.is_none()).count(), 1);
+                let mut searched = nodes.iter().filter(|node| node.kind != NodeKind::Duplicate);
+                assert!(searched.all(|node| (node.kind == NodeKind::Cutoff) == (node.g + node.h > bound)));
+            }
+        }
+    }
+
+    #[test]
+    fn trees_stop_at_the_cap() {
+        let board = seeded_boards(1).remove(0);
+        for tree in [a_star_tree(&board, &Board::goal(3, 3), &manhattan_distance, 50), ida_star_tree(&board, &Board::goal(3, 3), &manhattan_distance, 50)] {
+            assert!(tree.truncated);
+            assert_eq!(tree.nodes.len(), 50);
+            assert!(tree.solution.is_empty());
+            let mut dot = vec![];
+            write_dot(&mut dot, &tree).unwrap();
+            let dot = String::from_utf8(dot).unwrap();
+            assert!(dot.starts_with("digraph search {"));
+            assert!(dot.contains("truncated at 50 nodes"));
+        }
+    }
+}
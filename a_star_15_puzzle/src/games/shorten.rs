@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::games::heuristics::manhattan_distance_to;
+use crate::games::search;
+use crate::games::sliding::{Board, Move};
+
+// Define the result of shortening a solution
+#[derive(Clone, Debug)]
+pub struct Shortened {
+    pub moves: Vec<Move>,
+    pub original_length: usize,
+    pub cycles_removed: usize,
+    pub windows_improved: usize,
+    pub passes: usize,
+}
+
+fn boards_along(start: &Board, moves: &[Move]) -> Vec<Board> {
+    let mut boards = Vec::with_capacity(moves.len() + 1);
+    let mut board = start.clone();
+    boards.push(board.clone());
+    for &m in moves {
+        board.apply(m);
+        boards.push(board.clone());
+    }
+    boards
+}
+
+// Cut out every loop in the solution: whenever a board repeats, drop the moves in between
+pub fn remove_cycles(start: &Board, moves: &[Move]) -> (Vec<Move>, usize) {
+    let mut seen: HashMap<Board, usize> = HashMap::new();
+    let mut kept: Vec<Move> = vec![];
+    let mut boards = vec![start.clone()];
+    seen.insert(start.clone(), 0);
+    let mut cycles = 0;
+    for &m in moves {
+        let mut board = boards.last().unwrap().clone();
+        board.apply(m);
+        if let Some(&index) = seen.get(&board) {
+            for removed in boards.drain(index + 1..) {
+                seen.remove(&removed);
+            }
+            kept.truncate(index);
+            cycles += 1;
+        } else {
+            seen.insert(board.clone(), boards.len());
+            boards.push(board);
+            kept.push(m);
+        }
+    }
+    (kept, cycles)
+}
+
+// Shorten a valid move sequence: remove cycles, then re-solve every window of `window` moves
+// optimally with A* and splice in shorter paths, repeating until a pass finds nothing or the
+// time budget runs out
+pub fn shorten(start: &Board, moves: &[Move], window: usize, budget: Duration) -> Shortened {
+    let now = Instant::now();
+    let original_length = moves.len();
+    let (mut moves, mut cycles_removed) = remove_cycles(start, moves);
+    let mut windows_improved = 0;
+    let mut passes = 0;
+    let window = window.max(2);
+    'passes: while now.elapsed() < budget {
+        passes += 1;
+        let mut improved = false;
+        let mut i = 0;
+        let mut boards = boards_along(start, &moves);
+        while i + window <= moves.len() {
+            if now.elapsed() >= budget {
+                break 'passes;
+            }
+            let (from, to) = (&boards[i], &boards[i + window]);
+            let heuristic = |board: &Board| manhattan_distance_to(board, to);
+            if let Some(solution) = search::a_star_search(from, to, &heuristic) {
+                if solution.moves.len() < window {
+                    moves.splice(i..i + window, solution.moves);
+                    let (without_cycles, cycles) = remove_cycles(start, &moves);
+                    moves = without_cycles;
+                    cycles_removed += cycles;
+                    boards = boards_along(start, &moves);
+                    windows_improved += 1;
+                    improved = true;
+                    continue;
+                }
+            }
+            i += 1;
+        }
+        if !improved {
+            break;
+        }
+    }
+    Shortened {
+        moves,
+        original_length,
+        cycles_removed,
+        windows_improved,
+        passes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::heuristics::manhattan_distance;
+    use crate::games::test_support::{optimal_length, played, seeded_boards};
+    use rand::prelude::*;
+    use rand_pcg::Pcg64;
+
+    // A valid but wasteful solution: a random walk away from the start, then back to the goal
+    fn detour(start: &Board, seed: u64) -> Vec<Move> {
+        let mut rng = Pcg64::seed_from_u64(seed);
+        let mut board = start.clone();
+        let mut moves = vec![];
+        while moves.len() < 30 {
+            let (m, next) = board.successors().choose(&mut rng).unwrap().clone();
+            board = next;
+            moves.push(m);
+        }
+        moves.extend(search::a_star_search(&board, &Board::goal(3, 3), &manhattan_distance).unwrap().moves);
+        moves
+    }
+
+    #[test]
+    fn remove_cycles_keeps_the_end_board() {
+        let goal = Board::goal(3, 3);
+        for (seed, start) in seeded_boards(10).iter().enumerate() {
+            let moves = detour(start, seed as u64);
+            let (kept, _) = remove_cycles(start, &moves);
+            assert!(kept.len() <= moves.len());
+            assert_eq!(played(start, &kept), Some(goal.clone()));
+        }
+    }
+
+    #[test]
+    fn shortened_solutions_stay_valid_and_never_grow() {
+        let goal = Board::goal(3, 3);
+        for (seed, start) in seeded_boards(10).iter().enumerate() {
+            let moves = detour(start, seed as u64);
+            let shortened = shorten(start, &moves, 8, Duration::from_secs(60));
+            assert_eq!(shortened.original_length, moves.len());
+            assert!(shortened.moves.len() <= moves.len());
+            assert!(shortened.moves.len() as u32 >= optimal_length(start));
+            assert_eq!(played(start, &shortened.moves), Some(goal.clone()));
+        }
+    }
+}
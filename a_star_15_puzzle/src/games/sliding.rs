@@ -0,0 +1,230 @@
+use std::fmt;
+use rand::prelude::*;
+use rand_pcg::Pcg64;
+
+// Define the moves of the blank tile
+#[derive(Clone, Copy, Eq, Hash, PartialEq, Debug)]
+pub enum Move {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+pub const MOVES: [Move; 4] = [Move::Up, Move::Down, Move::Left, Move::Right];
+
+impl Move {
+    fn delta(self) -> (isize, isize) {
+        match self {
+            Move::Up => (-1, 0),
+            Move::Down => (1, 0),
+            Move::Left => (0, -1),
+            Move::Right => (0, 1),
+        }
+    }
+
+    pub fn opposite(self) -> Move {
+        match self {
+            Move::Up => Move::Down,
+            Move::Down => Move::Up,
+            Move::Left => Move::Right,
+            Move::Right => Move::Left,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Move::Up => 'U',
+            Move::Down => 'D',
+            Move::Left => 'L',
+            Move::Right => 'R',
+        }
+    }
+}
+
+// Write a sequence of blank moves as a string like "ULDR"
+pub fn moves_to_string(moves: &[Move]) -> String {
+    moves.iter().map(|m| m.to_char()).collect()
+}
+
+// Define a sliding puzzle board of any rows x cols size, stored row by row with 0 as the blank
+#[derive(Clone, Eq, Hash, PartialEq, Debug)]
+pub struct Board {
+    rows: usize,
+    cols: usize,
+    tiles: Vec<u16>,
+    blank: usize,
+}
+
+impl Board {
+    pub fn from_tiles(rows: usize, cols: usize, tiles: Vec<u16>) -> Self {
+        assert_eq!(tiles.len(), rows * cols, "board must have rows * cols tiles");
+        let blank = tiles.iter().position(|&t| t == 0).expect("board has no blank");
+        Self { rows, cols, tiles, blank }
+    }
+
+    // Define the goal state for the given size
+    pub fn goal(rows: usize, cols: usize) -> Self {
+        let mut tiles: Vec<u16> = (1..(rows * cols) as u16).collect();
+        tiles.push(0);
+        Self::from_tiles(rows, cols, tiles)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn tiles(&self) -> &[u16] {
+        &self.tiles
+    }
+
+    pub fn blank(&self) -> (usize, usize) {
+        (self.blank / self.cols, self.blank % self.cols)
+    }
+
+    // Cell the blank would move to, None if it would leave the board
+    pub fn target(&self, m: Move) -> Option<usize> {
+        let (x, y) = self.blank();
+        let (dx, dy) = m.delta();
+        let nx = x as isize + dx;
+        let ny = y as isize + dy;
+        if nx < 0 || ny < 0 || nx >= self.rows as isize || ny >= self.cols as isize {
+            return None;
+        }
+        Some(nx as usize * self.cols + ny as usize)
+    }
+
+    // Slide the blank in the given direction, returning false if it would leave the board
+    pub fn apply(&mut self, m: Move) -> bool {
+        let Some(target) = self.target(m) else { return false };
+        self.tiles.swap(self.blank, target);
+        self.blank = target;
+        true
+    }
+
+    // Tile that trades places with the blank when it moves in the given direction
+    pub fn tile_moved_by(&self, m: Move) -> Option<u16> {
+        self.target(m).map(|target| self.tiles[target])
+    }
+
+    pub fn moved(&self, m: Move) -> Option<Self> {
+        let mut next = self.clone();
+        if next.apply(m) {
+            Some(next)
+        } else {
+            None
+        }
+    }
+
+    pub fn successors(&self) -> Vec<(Move, Self)> {
+        MOVES.iter().filter_map(|&m| self.moved(m).map(|b| (m, b))).collect()
+    }
+}
+
+fn get_inversion_count(tiles: &[u16]) -> usize {
+    let mut inversion_count = 0;
+    for i in 0..tiles.len() {
+        for j in (i + 1)..tiles.len() {
+            if tiles[i] != 0 && tiles[j] != 0 && tiles[i] > tiles[j] {
+                inversion_count += 1;
+            }
+        }
+    }
+    inversion_count
+}
+
+//Computes the parity that no move can change: the inversion count, plus the row of the blank
+//on even widths where vertical moves jump over an odd number of tiles. The number of rows
+//does not matter, only the width does.
+fn solvability_parity(board: &Board) -> usize {
+    let inversion_count = get_inversion_count(&board.tiles);
+    if board.cols % 2 == 1 {
+        inversion_count % 2
+    } else {
+        (inversion_count + board.blank().0) % 2
+    }
+}
+
+//Checks if the goal board can be reached from the start board
+pub fn is_solvable_towards(start: &Board, goal: &Board) -> bool {
+    let mut start_tiles = start.tiles.clone();
+    let mut goal_tiles = goal.tiles.clone();
+    start_tiles.sort_unstable();
+    goal_tiles.sort_unstable();
+    start.rows == goal.rows && start.cols == goal.cols && start_tiles == goal_tiles && solvability_parity(start) == solvability_parity(goal)
+}
+
+//Checks if a board of any size can reach the standard goal state
+pub fn is_solvable(board: &Board) -> bool {
+    is_solvable_towards(board, &Board::goal(board.rows, board.cols))
+}
+
+//Shuffle a board of any size with Fisher–Yates shuffle
+pub fn random_board(rows: usize, cols: usize) -> Board {
+    shuffled_board(rows, cols, &mut Pcg64::from_entropy())
+}
+
+//Shuffle a board reproducibly, the same seed always gives the same board
+pub fn seeded_random_board(rows: usize, cols: usize, seed: u64) -> Board {
+    shuffled_board(rows, cols, &mut Pcg64::seed_from_u64(seed))
+}
+
+//Shuffle a board with the given generator
+pub fn shuffled_board(rows: usize, cols: usize, rng: &mut Pcg64) -> Board {
+    loop {
+        let mut tiles: Vec<u16> = (0..(rows * cols) as u16).collect();
+        tiles.shuffle(rng);
+        let board = Board::from_tiles(rows, cols, tiles);
+        if is_solvable(&board) {
+            return board;
+        }
+    }
+}
+
+//Shuffle a board of any size with n random moves, never undoing the previous move
+pub fn n_random_moves_from_goal(rows: usize, cols: usize, n: usize) -> Board {
+    walked_board(rows, cols, n, &mut Pcg64::from_entropy())
+}
+
+//Shuffle a board reproducibly with n random moves from the goal
+pub fn seeded_n_random_moves_from_goal(rows: usize, cols: usize, n: usize, seed: u64) -> Board {
+    walked_board(rows, cols, n, &mut Pcg64::seed_from_u64(seed))
+}
+
+//Walk n random moves from the goal with the given generator
+pub fn walked_board(rows: usize, cols: usize, n: usize, rng: &mut Pcg64) -> Board {
+    let mut board = Board::goal(rows, cols);
+    let mut last: Option<Move> = None;
+    for _ in 0..n {
+        let moves: Vec<Move> = board
+            .successors()
+            .into_iter()
+            .map(|(m, _)| m)
+            .filter(|&m| Some(m.opposite()) != last)
+            .collect();
+        let m = *moves.choose(rng).unwrap();
+        board.apply(m);
+        last = Some(m);
+    }
+    board
+}
+
+impl<const R: usize, const C: usize> From<[[u8; C]; R]> for Board {
+    fn from(state: [[u8; C]; R]) -> Self {
+        let tiles = state.iter().flat_map(|row| row.iter().map(|&t| t as u16)).collect();
+        Self::from_tiles(R, C, tiles)
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.tiles.chunks(self.cols) {
+            writeln!(f, "{:?}", row)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use crate::games::sliding::Board;
+
+// Boards up to this many cells fit a visited table indexed by permutation rank (12! bytes)
+pub const MAX_CELLS: usize = 12;
+
+// Define the number of boards at every distance from the goal, found by exhaustive search
+#[derive(Clone, Debug)]
+pub struct DepthDistribution {
+    pub counts: Vec<u64>,
+    pub hardest: Vec<Board>,
+}
+
+impl DepthDistribution {
+    pub fn states(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    pub fn diameter(&self) -> usize {
+        self.counts.len() - 1
+    }
+}
+
+// Rank a permutation of 0..n in lexicographic order (Lehmer code)
+pub fn rank(tiles: &[u16]) -> usize {
+    let mut rank = 0;
+    for i in 0..tiles.len() {
+        let smaller = tiles[i + 1..].iter().filter(|&&t| t < tiles[i]).count();
+        rank = rank * (tiles.len() - i) + smaller;
+    }
+    rank
+}
+
+pub fn unrank(mut rank: usize, n: usize) -> Vec<u16> {
+    let mut digits = vec![0; n];
+    for i in (0..n).rev() {
+        digits[i] = rank % (n - i);
+        rank /= n - i;
+    }
+    let mut left: Vec<u16> = (0..n as u16).collect();
+    digits.into_iter().map(|digit| left.remove(digit)).collect()
+}
+
+// Marks the boards of a distance table that cannot reach the goal
+pub const UNREACHABLE: u8 = u8::MAX;
+
+// Breadth-first search over every board reachable from the goal, one layer at a time, giving
+// the distance of every board indexed by its rank. Returns None for boards with more than
+// MAX_CELLS cells.
+pub fn distance_table(goal: &Board) -> Option<Vec<u8>> {
+    let (rows, cols) = (goal.rows(), goal.cols());
+    let cells = rows * cols;
+    if cells > MAX_CELLS {
+        return None;
+    }
+    let total: usize = (1..=cells).product();
+    let mut distances = vec![UNREACHABLE; total];
+    let mut layer = vec![rank(goal.tiles()) as u32];
+    distances[layer[0] as usize] = 0;
+    let mut depth = 0;
+    while !layer.is_empty() {
+        depth += 1;
+        let mut next = vec![];
+        for &state in &layer {
+            let board = Board::from_tiles(rows, cols, unrank(state as usize, cells));
+            for (_, successor) in board.successors() {
+                let index = rank(successor.tiles());
+                if distances[index] == UNREACHABLE {
+                    distances[index] = depth;
+                    next.push(index as u32);
+                }
+            }
+        }
+        layer = next;
+    }
+    Some(distances)
+}
+
+// Count the boards at every distance from the goal and collect the farthest ones.
+// Returns None for boards with more than MAX_CELLS cells.
+pub fn depth_distribution(goal: &Board) -> Option<DepthDistribution> {
+    let (rows, cols) = (goal.rows(), goal.cols());
+    let distances = distance_table(goal)?;
+    let mut counts = vec![];
+    for &distance in distances.iter().filter(|&&distance| distance != UNREACHABLE) {
+        if counts.len() <= distance as usize {
+            counts.resize(distance as usize + 1, 0);
+        }
+        counts[distance as usize] += 1;
+    }
+    let diameter = counts.len() - 1;
+    let hardest = distances
+        .iter()
+        .enumerate()
+        .filter(|&(_, &distance)| distance as usize == diameter)
+        .map(|(state, _)| Board::from_tiles(rows, cols, unrank(state, rows * cols)))
+        .collect();
+    Some(DepthDistribution { counts, hardest })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank_and_unrank_round_trip() {
+        for index in 0..720 {
+            assert_eq!(rank(&unrank(index, 6)), index);
+        }
+        let goal = Board::goal(3, 4);
+        assert_eq!(unrank(rank(goal.tiles()), 12), goal.tiles());
+    }
+
+    #[test]
+    fn rectangular_boards_have_known_diameters() {
+        // Half of the permutations are reachable, and 2x3 and 3x2 boards are 21 moves across
+        for (rows, cols, diameter) in [(2, 2, 6), (2, 3, 21), (3, 2, 21), (3, 3, 31)] {
+            let distribution = depth_distribution(&Board::goal(rows, cols)).unwrap();
+            let states: u64 = (1..=(rows * cols) as u64).product();
+            assert_eq!(distribution.states(), states / 2, "{}x{}", rows, cols);
+            assert_eq!(distribution.diameter(), diameter, "{}x{}", rows, cols);
+            assert!(distribution.hardest.iter().all(|board| board.rows() == rows && board.cols() == cols));
+        }
+        assert!(distance_table(&Board::goal(4, 4)).is_none());
+    }
+}
@@ -0,0 +1,35 @@
+use std::fs;
+use std::path::PathBuf;
+
+use once_cell::sync::Lazy;
+
+use crate::games::sliding::{self, Board, Move};
+use crate::games::state_space::{self, rank};
+
+// Distance of every 3x3 board from the standard goal, shared by the tests of every search
+static DISTANCES_3X3: Lazy<Vec<u8>> = Lazy::new(|| state_space::distance_table(&Board::goal(3, 3)).unwrap());
+
+// Optimal number of moves from a solvable 3x3 board to the standard goal
+pub fn optimal_length(board: &Board) -> u32 {
+    DISTANCES_3X3[rank(board.tiles())] as u32
+}
+
+// Shuffled 3x3 boards drawn from the seeds 0..count
+pub fn seeded_boards(count: u64) -> Vec<Board> {
+    (0..count).map(|seed| sliding::seeded_random_board(3, 3, seed)).collect()
+}
+
+// Board reached by playing the moves from the start, None if a move leaves the board
+pub fn played(start: &Board, moves: &[Move]) -> Option<Board> {
+    let mut board = start.clone();
+    moves.iter().all(|&m| board.apply(m)).then_some(board)
+}
+
+// Path in the temporary directory named for this process, keeping the extension of `name`,
+// with anything a previous run left there removed
+pub fn temporary_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("{}_{}", std::process::id(), name));
+    let _ = fs::remove_file(&path);
+    let _ = fs::remove_dir_all(&path);
+    path
+}
@@ -1,43 +1,63 @@
-use std::collections::{VecDeque, HashSet, HashMap};  
+use std::collections::{VecDeque, HashMap};
+use std::sync::{Arc, Mutex};
+use once_cell::sync::Lazy;
 
-#[derive(Debug, Eq, Hash, PartialEq, Clone, Copy)]
+// One table for every line the blank can occupy in the goal state
+static LOOKUP_WD_TABLES: [Lazy<HashMap<[[u8; 4]; 4], u8>>; 4] = [
+    Lazy::new(|| simulation(0)),
+    Lazy::new(|| simulation(1)),
+    Lazy::new(|| simulation(2)),
+    Lazy::new(|| simulation(3)),
+];
+
+// Define a walking distance table keyed by the flattened count matrix
+type GeneralTable = Arc<HashMap<Vec<u8>, u8>>;
+type GeneralTables = HashMap<(usize, usize, usize), GeneralTable>;
+
+// Tables for other board shapes, built on first use and keyed by (lines, line length, blank line)
+static GENERAL_WD_TABLES: Lazy<Mutex<GeneralTables>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Get the walking distance table for a goal with the blank in the given row (or column)
+pub fn lookup_table(blank_line: usize) -> &'static HashMap<[[u8; 4]; 4], u8> {
+    &LOOKUP_WD_TABLES[blank_line]
+}
+
+// Get the walking distance table for `lines` lines of `line_length` cells each, where the
+// key is the lines x lines matrix (row by row) counting the tiles of line i that belong in line j
+pub fn general_lookup_table(lines: usize, line_length: usize, blank_line: usize) -> GeneralTable {
+    let mut tables = GENERAL_WD_TABLES.lock().unwrap();
+    tables
+        .entry((lines, line_length, blank_line))
+        .or_insert_with(|| Arc::new(general_simulation(lines, line_length, blank_line)))
+        .clone()
+}
+
+#[derive(Debug, Eq, Hash, PartialEq, Clone)]
 struct Node{
-    board: [[u8; 4]; 4],
+    board: Vec<u8>,
+    blank_line: usize,
     h: u8
 }
 
 impl Node {
-    fn successors(&self) -> Vec<Self> {
-        let mut empty_slot: usize = 0;
+    // Move one tile from a line next to the blank into the blank's line
+    fn successors(&self, lines: usize) -> Vec<Self> {
         let mut succesors: Vec<Self> = Vec::new();
-        let mut counter: u8 = 0;
-        for i in 0..4 {
-            for j in 0..4 {
-                counter += self.board[i][j];
-            }
-            if counter == 3{
-                empty_slot = i;
-                break;
-            }
-            counter = 0;
+        let empty_slot = self.blank_line;
+        let mut neighbours = vec![];
+        if empty_slot + 1 < lines {
+            neighbours.push(empty_slot + 1);
         }
-        if empty_slot != 3 {
-            for i in 0..4 {
-                let mut tmp = self.board.clone();
-                if tmp[empty_slot + 1][i] != 0 {
-                    tmp[empty_slot + 1][i] -= 1;
-                    tmp[empty_slot][i] += 1;
-                    succesors.push(Self{board: tmp, h: self.h+1});
-                }
-            }
+        if empty_slot != 0 {
+            neighbours.push(empty_slot - 1);
         }
-        if empty_slot != 0{
-            for i in 0..4 {
-                let mut tmp = self.board.clone();
-                if tmp[empty_slot - 1][i] != 0 {
-                    tmp[empty_slot - 1][i] -= 1;
-                    tmp[empty_slot][i] += 1;
-                    succesors.push(Self{board: tmp, h: self.h+1});
+        for line in neighbours {
+            for i in 0..lines {
+                if self.board[line * lines + i] != 0 {
+                    let mut tmp = self.board.clone();
+                    tmp[line * lines + i] -= 1;
+                    tmp[empty_slot * lines + i] += 1;
+                    succesors.push(Self{board: tmp, blank_line: line, h: self.h + 1});
                 }
             }
         }
@@ -45,38 +65,40 @@ impl Node {
     }
 }
 
-pub fn simulation() -> HashMap<[[u8; 4]; 4], u8>{
-    let mut goal_board: [[u8; 4]; 4] = [[0 ;4]; 4];
-    // Initialize the board
-    goal_board[0][0] = 4;
-    goal_board[1][1] = 4;
-    goal_board[2][2] = 4;
-    goal_board[3][3] = 3;
-    let starting_node = Node{board: goal_board, h: 0};
+fn general_simulation(lines: usize, line_length: usize, blank_line: usize) -> HashMap<Vec<u8>, u8>{
+    let mut goal_board = vec![0; lines * lines];
+    // Initialize the board, the line holding the blank has one tile less
+    for i in 0..lines {
+        goal_board[i * lines + i] = line_length as u8;
+    }
+    goal_board[blank_line * lines + blank_line] -= 1;
+    let starting_node = Node{board: goal_board, blank_line, h: 0};
     // Perform breadth-first search
     let mut queue = VecDeque::new();
-    let mut explored = HashSet::new();
-    // Add the initial state to the queue and the explored set
+    let mut lookup_table = HashMap::new();
+    // Add the initial state to the queue and the table
+    lookup_table.insert(starting_node.board.clone(), 0);
     queue.push_back(starting_node);
-    explored.insert(goal_board);
-    let mut unique_boards: Vec<Node> = Vec::new(); 
-    unique_boards.push(starting_node);
     while let Some(board) = queue.pop_front() {
-
-        // Generate the next states and add them to the queue and the explored set
-        for next_board in board.successors() {
-            if !explored.contains(&next_board.board) {
+        // Generate the next states and add them to the queue and the table
+        for next_board in board.successors(lines) {
+            if !lookup_table.contains_key(&next_board.board) {
+                lookup_table.insert(next_board.board.clone(), next_board.h);
                 queue.push_back(next_board);
-                explored.insert(next_board.board);
-                //println!("{:?}", &next_board);
-                unique_boards.push(next_board);
             }
         }
     }
-    //println!("{:?}", unique_boards.len());
+    lookup_table
+}
+
+pub fn simulation(blank_line: usize) -> HashMap<[[u8; 4]; 4], u8>{
     let mut lookup_table = HashMap::new();
-    for entry in unique_boards {
-        lookup_table.insert(entry.board, entry.h);
+    for (board, h) in general_simulation(4, 4, blank_line) {
+        let mut key = [[0; 4]; 4];
+        for i in 0..4 {
+            key[i].copy_from_slice(&board[i * 4..(i + 1) * 4]);
+        }
+        lookup_table.insert(key, h);
     }
-    return lookup_table;
+    lookup_table
 }
@@ -1,13 +1,42 @@
 mod games;
 use games::puzzle8;
 use games::puzzle15;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::path::Path;
 use std::thread;
-use std::time::Instant;
-use dialoguer::{console::Term, theme::ColorfulTheme, Select};
+use std::time::{Duration, Instant};
+use dialoguer::{console::Term, theme::ColorfulTheme, Input, MultiSelect, Select};
 use games::puzzle15_WD;
+use games::real_time::{self, RealTimeAgent};
+use games::constructive;
+use games::optimal_solutions::{self, OptimalSolutions};
+use games::search::{self, SearchObserver, SearchOptions, Solution};
+use games::shorten;
+use games::heuristics::{self, manhattan_distance, manhattan_distance_to, Heuristic, LinearConflict, Manhattan, MaxOf, Scaled, SumOf, WalkingDistance};
+use games::sliding::{self, moves_to_string, Board};
+use games::state_space;
+use games::puzzle24;
+use games::cost::CostModel;
+use games::lookups::{Selection, SymmetricLookups};
+use games::pattern_database::{AdditivePatternDatabase, PatternDatabase};
+use games::open_list::TieBreaking;
+use games::memory_bounded;
+use games::epea::{self, ManhattanOperators};
+use games::external_bfs;
+use games::generator::Generator;
+use games::heuristic_report::{self, HeuristicReport};
+use games::learned::{LearnedHeuristic, Training};
+use games::render;
+use games::play::{self, Game};
+use games::search_tree::{self, NodeKind};
+use games::observers::{Broadcast, EventHistogram, JsonTrace, ProgressBar};
+use games::checkpoint::{self, Checkpoint, SaveOptions};
+use games::batch::{self, Algorithm, BatchOptions};
 
 fn main() {
-    let items = vec!["8 Puzzle form shuffle", "15 puzzle from n moves", "15 Puzzle form shuffle", "IDA* from shuffle", "Collect Data", "Collect Data2", "Exit"];
+    let items = vec!["8 Puzzle form shuffle", "15 puzzle from n moves", "15 Puzzle form shuffle", "IDA* from shuffle", "Collect Data", "Collect Data2", "Real-time trials", "Beam / greedy search", "Row-by-row solver", "Shorten solution", "Count optimal solutions", "Solve board to board", "Rectangular board", "State space depths", "24 puzzle with pattern databases", "Weighted tiles", "Inconsistent heuristics", "Dual lookups", "Tie-breaking", "Memory-bounded search", "Partial expansion A*", "External-memory BFS", "Generate instances", "Heuristic quality report", "Combine heuristics", "Train learned heuristic", "Play the puzzle", "Search tree to DOT", "Observe a search", "Resumable IDA*", "Batch solve instance file", "Exit"];
     loop {
         let selection = Select::with_theme(&ColorfulTheme::default())
         .items(&items)
@@ -22,81 +51,98 @@ fn main() {
             4 => ida(),
             5 => collect_data(),
             6 => collect_data2(),
+            7 => real_time_trials(),
+            8 => beam_and_greedy(),
+            9 => row_by_row(),
+            10 => shorten_solution(),
+            11 => count_optimal(),
+            12 => board_to_board(),
+            13 => rectangular_board(),
+            14 => state_space_depths(),
+            15 => puzzle24_with_pdb(),
+            16 => weighted_tiles(),
+            17 => inconsistent_heuristics(),
+            18 => dual_lookups(),
+            19 => tie_breaking(),
+            20 => memory_bounded_search(),
+            21 => partial_expansion(),
+            22 => external_memory_bfs(),
+            23 => generate_instances(),
+            24 => heuristic_quality_report(),
+            25 => combine_heuristics(),
+            26 => train_learned_heuristic(),
+            27 => play_puzzle(),
+            28 => search_tree_to_dot(),
+            29 => observe_search(),
+            30 => resumable_ida(),
+            31 => batch_solve(),
             _ => break
         }
     }
 }
 
 fn puzzle8_from_random() {
-    let start_state: [[u8; 3]; 3] = puzzle8::random_state();
-    if let Some(path) = puzzle8::a_star_search(start_state) {
-        for node in &path{
-            for row in node{
-                println!("{:?}", row);
-            }
-            println!("");
-        }
-        println!("path length = {}", path.len());
-    } else {
-        println!("Goal state not found.");
+    let start_state: [[u8; 3]; 3] = puzzle8::random_state(puzzle8::GOAL_STATE);
+    let path = puzzle8::a_star_search(start_state, puzzle8::GOAL_STATE);
+    match &path {
+        Some(path) => println!("path length = {}", path.len()),
+        None => println!("Goal state not found."),
+    }
+    if let Some(path) = path {
+        let frames: Vec<Board> = path.iter().map(|&state| Board::from(state)).collect();
+        render::play(&Term::stdout(), &frames).expect("failed");
     }
     println!("\n");
 }
 
 fn puzzle15_from_random() {
-    let start_state: [[u8; 4]; 4] = puzzle15::random_state();
+    let start_state: [[u8; 4]; 4] = puzzle15::random_state(puzzle15::GOAL_STATE);
     let now = Instant::now();
-    if let Some(path) = puzzle15::a_star_search(start_state) {
-        for node in &path{
-            for row in node{
-                println!("{:?}", row);
-            }
-            println!("");
-        }
-        println!("path length = {}", path.len());
-    } else {
-        println!("Goal state not found.");
-    }
+    let path = puzzle15::a_star_search(start_state, puzzle15::GOAL_STATE);
     let elapsed = now.elapsed();
+    match &path {
+        Some(path) => println!("path length = {}", path.len()),
+        None => println!("Goal state not found."),
+    }
     println!("Elapsed: {:.2?}", elapsed); 
+    if let Some(path) = path {
+        let frames: Vec<Board> = path.iter().map(|&state| Board::from(state)).collect();
+        render::play(&Term::stdout(), &frames).expect("failed");
+    }
     println!("\n");
 }
 
 fn puzzle15_from_n_moves() {
-    let start_state: [[u8; 4]; 4] = puzzle15::n_random_moves_from_goal(50);
+    let start_state: [[u8; 4]; 4] = puzzle15::n_random_moves_from_goal(50, puzzle15::GOAL_STATE);
     let now = Instant::now();
-    if let Some(path) = puzzle15::a_star_search(start_state) {
-        for node in &path{
-            for row in node{
-                println!("{:?}", row);
-            }
-            println!("");
-        }
-        println!("path length = {}", path.len());
-    } else {
-        println!("Goal state not found.");
-    }
+    let path = puzzle15::a_star_search(start_state, puzzle15::GOAL_STATE);
     let elapsed = now.elapsed();
+    match &path {
+        Some(path) => println!("path length = {}", path.len()),
+        None => println!("Goal state not found."),
+    }
     println!("Elapsed: {:.2?}", elapsed);
+    if let Some(path) = path {
+        let frames: Vec<Board> = path.iter().map(|&state| Board::from(state)).collect();
+        render::play(&Term::stdout(), &frames).expect("failed");
+    }
     println!("\n");
 }
 
 fn ida() {
-    let start_state: [[u8; 4]; 4] = puzzle15::random_state();
+    let start_state: [[u8; 4]; 4] = puzzle15::random_state(puzzle15::GOAL_STATE);
     let now = Instant::now();
-    if let Some(path) = puzzle15::ida_star_search(start_state) {
-        for node in &path{
-            for row in node{
-                println!("{:?}", row);
-            }
-            println!("");
-        }
-        println!("path length = {}", path.len());
-    } else {
-        println!("Goal state not found.");
-    }
+    let path = puzzle15::ida_star_search(start_state, puzzle15::GOAL_STATE);
     let elapsed = now.elapsed();
+    match &path {
+        Some(path) => println!("path length = {}", path.len()),
+        None => println!("Goal state not found."),
+    }
     println!("Elapsed: {:.2?}", elapsed); 
+    if let Some(path) = path {
+        let frames: Vec<Board> = path.iter().map(|&state| Board::from(state)).collect();
+        render::play(&Term::stdout(), &frames).expect("failed");
+    }
     println!("\n");
 }
 
@@ -104,10 +150,10 @@ fn collect_data(){
     let mut handles = Vec::new();
     for _ in 0..25 {
         let handle = thread::spawn(move || {
-            let start_state: [[u8; 4]; 4] = puzzle15::random_state();
+            let start_state: [[u8; 4]; 4] = puzzle15::random_state(puzzle15::GOAL_STATE);
             //println!("start");
             let now = Instant::now();
-            if let Some(path) = puzzle15::ida_star_search(start_state) {
+            if let Some(path) = puzzle15::ida_star_search(start_state, puzzle15::GOAL_STATE) {
                 println!("path length = {}", path.len());
             } else {
                 println!("Goal state not found.");
@@ -126,11 +172,11 @@ fn collect_data(){
 
 fn collect_data2() {
 
-    let start_state: [[u8; 4]; 4] = puzzle15_WD::n_random_moves_from_goal(40);
+    let start_state: [[u8; 4]; 4] = puzzle15_WD::n_random_moves_from_goal(40, puzzle15_WD::GOAL_STATE);
     //println!("start");
 
     let now = Instant::now();
-    if let Some(path) = puzzle15::a_star_search(start_state) {
+    if let Some(path) = puzzle15::a_star_search(start_state, puzzle15::GOAL_STATE) {
         println!("path length = {}", path.len());
     } else {
         println!("Goal state not found.");
@@ -140,7 +186,7 @@ fn collect_data2() {
     println!("\n");
 
     let now = Instant::now();
-    if let Some(path) = puzzle15_WD::a_star_search(start_state) {
+    if let Some(path) = puzzle15_WD::a_star_search(start_state, puzzle15_WD::GOAL_STATE) {
         println!("path length = {}", path.len());
     } else {
         println!("Goal state not found.");
@@ -150,3 +196,1263 @@ fn collect_data2() {
     println!("\n");
 }
 
+
+fn real_time_trials() {
+    let variants = vec!["LRTA*", "RTA*"];
+    let variant = match Select::with_theme(&ColorfulTheme::default())
+        .items(&variants)
+        .default(0)
+        .interact_on(&Term::stderr())
+        .expect("failed")
+    {
+        0 => real_time::Variant::Lrta,
+        _ => real_time::Variant::Rta,
+    };
+    let lookahead: u32 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Lookahead depth")
+        .default(3)
+        .interact_text()
+        .expect("failed");
+    let n: u16 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Random moves from goal")
+        .default(30)
+        .interact_text()
+        .expect("failed");
+    let trials: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Number of trials")
+        .default(200)
+        .interact_text()
+        .expect("failed");
+
+    let start = Board::from(puzzle15::n_random_moves_from_goal(n, puzzle15::GOAL_STATE));
+    print!("{}", start);
+    let mut agent = RealTimeAgent::new(variant, lookahead, Board::goal(4, 4));
+    let now = Instant::now();
+    let results = real_time::run_trials(&mut agent, &start, trials, 100_000, 5);
+    for (i, trial) in results.iter().enumerate() {
+        if trial.solved {
+            println!("trial {:>3}: path length = {:>6}, learned states = {}", i + 1, trial.moves.len(), trial.learned_states);
+        } else {
+            println!("trial {:>3}: gave up after {} moves, learned states = {}", i + 1, trial.moves.len(), trial.learned_states);
+        }
+    }
+    if let Some(last) = results.last() {
+        if last.solved {
+            println!("last solution = {}", moves_to_string(&last.moves));
+        }
+    }
+    let elapsed = now.elapsed();
+    println!("Elapsed: {:.2?}", elapsed);
+    println!("\n");
+}
+
+fn report(name: &str, solution: Option<Solution>, now: Instant) {
+    let elapsed = now.elapsed();
+    if let Some(solution) = solution {
+        println!("{}: path length = {}, expanded = {}, generated = {}, elapsed: {:.2?}", name, solution.moves.len(), solution.stats.expanded, solution.stats.generated, elapsed);
+    } else {
+        println!("{}: goal state not found, elapsed: {:.2?}", name, elapsed);
+    }
+}
+
+// Read the number of rows and columns of a board
+fn read_dimensions(default_rows: usize, default_cols: usize) -> (usize, usize) {
+    let rows: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Rows")
+        .default(default_rows)
+        .validate_with(|n: &usize| if *n >= 2 { Ok(()) } else { Err("a board needs at least 2 rows") })
+        .interact_text()
+        .expect("failed");
+    let cols: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Columns")
+        .default(default_cols)
+        .validate_with(|n: &usize| if *n >= 2 { Ok(()) } else { Err("a board needs at least 2 columns") })
+        .interact_text()
+        .expect("failed");
+    (rows, cols)
+}
+
+fn beam_and_greedy() {
+    let (rows, cols) = read_dimensions(6, 6);
+    let n: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Random moves from goal")
+        .default(200)
+        .interact_text()
+        .expect("failed");
+    let width: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Beam width")
+        .default(1000)
+        .interact_text()
+        .expect("failed");
+
+    let start = sliding::n_random_moves_from_goal(rows, cols, n);
+    let goal = Board::goal(rows, cols);
+    print!("{}", start);
+    if rows * cols <= 16 {
+        let now = Instant::now();
+        report("A*", search::a_star_search(&start, &goal, &manhattan_distance), now);
+    }
+    let now = Instant::now();
+    report("Greedy best-first", search::greedy_best_first_search(&start, &goal, &manhattan_distance), now);
+    let now = Instant::now();
+    report("Beam search", search::beam_search(&start, &goal, &manhattan_distance, width, 100_000), now);
+    println!("\n");
+}
+
+fn row_by_row() {
+    let (rows, cols) = read_dimensions(10, 10);
+
+    let start = sliding::random_board(rows, cols);
+    print!("{}", start);
+    let now = Instant::now();
+    if let Some(moves) = constructive::solve(&start) {
+        println!("moves = {}", moves_to_string(&moves));
+        println!("path length = {}", moves.len());
+    } else {
+        println!("Board is not solvable.");
+    }
+    let elapsed = now.elapsed();
+    println!("Elapsed: {:.2?}", elapsed);
+    println!("\n");
+}
+
+fn shorten_solution() {
+    let (rows, cols) = read_dimensions(6, 6);
+    let solvers = vec!["Row-by-row", "Greedy best-first"];
+    let solver = Select::with_theme(&ColorfulTheme::default())
+        .items(&solvers)
+        .default(0)
+        .interact_on(&Term::stderr())
+        .expect("failed");
+    let window: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Window length")
+        .default(12)
+        .interact_text()
+        .expect("failed");
+    let seconds: u64 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Time budget in seconds")
+        .default(10)
+        .interact_text()
+        .expect("failed");
+
+    let start = sliding::random_board(rows, cols);
+    print!("{}", start);
+    let goal = Board::goal(rows, cols);
+    let moves = match solver {
+        0 => constructive::solve(&start),
+        _ => search::greedy_best_first_search(&start, &goal, &manhattan_distance).map(|solution| solution.moves),
+    };
+    let Some(moves) = moves else {
+        println!("Goal state not found.");
+        return;
+    };
+    let now = Instant::now();
+    let shortened = shorten::shorten(&start, &moves, window, Duration::from_secs(seconds));
+    let elapsed = now.elapsed();
+    let saved = shortened.original_length - shortened.moves.len();
+    println!("moves = {}", moves_to_string(&shortened.moves));
+    println!("path length = {} -> {} ({} shorter, {:.1}%)", shortened.original_length, shortened.moves.len(), saved, 100.0 * saved as f64 / shortened.original_length.max(1) as f64);
+    println!("cycles removed = {}, windows improved = {}, passes = {}", shortened.cycles_removed, shortened.windows_improved, shortened.passes);
+    println!("Elapsed: {:.2?}", elapsed);
+    println!("\n");
+}
+
+fn count_optimal() {
+    let puzzles = vec!["8 Puzzle form shuffle", "15 puzzle from n moves"];
+    let start = match Select::with_theme(&ColorfulTheme::default())
+        .items(&puzzles)
+        .default(0)
+        .interact_on(&Term::stderr())
+        .expect("failed")
+    {
+        0 => Board::from(puzzle8::random_state(puzzle8::GOAL_STATE)),
+        _ => Board::from(puzzle15::n_random_moves_from_goal(50, puzzle15::GOAL_STATE)),
+    };
+    let cap: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Solutions to list")
+        .default(20)
+        .interact_text()
+        .expect("failed");
+
+    let goal = Board::goal(start.rows(), start.cols());
+    print!("{}", start);
+    let now = Instant::now();
+    if let Some(counted) = optimal_solutions::count_optimal_solutions(&start, &goal, &manhattan_distance) {
+        println!("path length = {}, optimal solutions = {}", counted.length, counted.count);
+        if let Some(solutions) = OptimalSolutions::new(&start, &goal, &manhattan_distance, Some(cap)) {
+            for solution in solutions {
+                println!("{}", solution);
+            }
+        }
+    } else {
+        println!("Goal state not found.");
+    }
+    let elapsed = now.elapsed();
+    println!("Elapsed: {:.2?}", elapsed);
+    println!("\n");
+}
+
+// Read a board typed as tiles separated by spaces, row by row, with 0 as the blank
+fn read_board(prompt: &str, rows: usize, cols: usize) -> Option<Board> {
+    let text: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .interact_text()
+        .expect("failed");
+    let tiles: Vec<u16> = text.split_whitespace().filter_map(|tile| tile.parse().ok()).collect();
+    let mut sorted = tiles.clone();
+    sorted.sort_unstable();
+    if rows * cols < 2 || rows * cols != tiles.len() || sorted.iter().enumerate().any(|(i, &tile)| tile as usize != i) {
+        println!("A board needs the tiles 0..rows*cols-1.");
+        return None;
+    }
+    Some(Board::from_tiles(rows, cols, tiles))
+}
+
+fn board_to_board() {
+    let (rows, cols) = read_dimensions(3, 3);
+    let Some(start) = read_board("Start board", rows, cols) else { return };
+    let Some(goal) = read_board("Goal board", rows, cols) else { return };
+    print!("{}\n{}", start, goal);
+    if !sliding::is_solvable_towards(&start, &goal) {
+        println!("Goal board cannot be reached from the start board.");
+        println!("\n");
+        return;
+    }
+    let now = Instant::now();
+    let heuristic = |board: &Board| manhattan_distance_to(board, &goal);
+    let solution = search::ida_star_search(&start, &goal, &heuristic);
+    let moves = solution.as_ref().map(|solution| solution.moves.clone());
+    if let Some(moves) = &moves {
+        println!("moves = {}", moves_to_string(moves));
+    }
+    report("IDA*", solution, now);
+    if let Some(moves) = moves {
+        render::play(&Term::stdout(), &render::frames(&start, &moves)).expect("failed");
+    }
+    println!("\n");
+}
+
+fn rectangular_board() {
+    let (rows, cols) = read_dimensions(3, 4);
+
+    let start = sliding::random_board(rows, cols);
+    let goal = Board::goal(rows, cols);
+    print!("{}", start);
+    let now = Instant::now();
+    let linear_conflict = |board: &Board| manhattan_distance(board) + heuristics::linear_conflict_to(board, &goal);
+    report("IDA* Manhattan + linear conflict", search::ida_star_search(&start, &goal, &linear_conflict), now);
+    let now = Instant::now();
+    let walking_distance = WalkingDistance::new(&goal);
+    report("IDA* walking distance", search::ida_star_search(&start, &goal, &|board| walking_distance.estimate(board)), now);
+    println!("\n");
+}
+
+fn state_space_depths() {
+    let (rows, cols) = read_dimensions(2, 5);
+
+    let now = Instant::now();
+    let Some(distribution) = state_space::depth_distribution(&Board::goal(rows, cols)) else {
+        println!("Boards with more than {} cells are too large to enumerate.", state_space::MAX_CELLS);
+        return;
+    };
+    for (depth, count) in distribution.counts.iter().enumerate() {
+        println!("depth {:>3}: {}", depth, count);
+    }
+    println!("states = {}, diameter = {}, hardest boards = {}", distribution.states(), distribution.diameter(), distribution.hardest.len());
+    if let Some(hardest) = distribution.hardest.first() {
+        print!("{}", hardest);
+    }
+    let elapsed = now.elapsed();
+    println!("Elapsed: {:.2?}", elapsed);
+    println!("\n");
+}
+
+fn puzzle24_with_pdb() {
+    let sources = vec!["Random board", "Seeded board", "Seeded n moves from goal", "Benchmark file"];
+    let source = Select::with_theme(&ColorfulTheme::default())
+        .items(&sources)
+        .default(0)
+        .interact_on(&Term::stderr())
+        .expect("failed");
+    let (goal, instances) = match source {
+        0 => (puzzle24::goal(), vec![puzzle24::random_state()]),
+        1 | 2 => {
+            let seed: u64 = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Seed")
+                .default(0)
+                .interact_text()
+                .expect("failed");
+            if source == 1 {
+                (puzzle24::goal(), vec![puzzle24::seeded_state(seed)])
+            } else {
+                let n: usize = Input::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Random moves from goal")
+                    .default(60)
+                    .interact_text()
+                    .expect("failed");
+                (puzzle24::goal(), vec![puzzle24::seeded_n_random_moves_from_goal(n, seed)])
+            }
+        }
+        _ => {
+            let path: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Instance file")
+                .interact_text()
+                .expect("failed");
+            let goals = vec!["Blank first (0 1 2 ... 24)", "Blank last (1 2 ... 24 0)"];
+            let goal = match Select::with_theme(&ColorfulTheme::default())
+                .items(&goals)
+                .default(0)
+                .interact_on(&Term::stderr())
+                .expect("failed")
+            {
+                0 => puzzle24::blank_first_goal(),
+                _ => puzzle24::goal(),
+            };
+            match puzzle24::load_instances(Path::new(&path)) {
+                Ok(instances) => (goal, instances),
+                Err(error) => {
+                    println!("Could not load instances: {}", error);
+                    return;
+                }
+            }
+        }
+    };
+    let dir: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Pattern database directory")
+        .default("pdb".to_string())
+        .interact_text()
+        .expect("failed");
+
+    let now = Instant::now();
+    let databases = puzzle24::pattern_databases(&goal, Path::new(&dir), &mut |i, pattern| {
+        println!("Loading pattern database {}/4 for tiles {:?}", i + 1, pattern);
+    });
+    let databases = match databases {
+        Ok(databases) => databases,
+        Err(error) => {
+            println!("Could not load pattern databases: {}", error);
+            return;
+        }
+    };
+    println!("Pattern databases ready, elapsed: {:.2?}", now.elapsed());
+    for (i, start) in instances.iter().enumerate() {
+        print!("{}", start);
+        let now = Instant::now();
+        let solution = puzzle24::ida_star_search(start, &goal, &databases, &mut |bound, stats| {
+            print!("\rbound = {}, expanded = {}, elapsed: {:.2?}", bound, stats.expanded, now.elapsed());
+            io::stdout().flush().ok();
+        });
+        println!();
+        if let Some(solution) = &solution {
+            println!("moves = {}", moves_to_string(&solution.moves));
+        }
+        report(&format!("Instance {}", i + 1), solution, now);
+    }
+    println!("\n");
+}
+
+// Read a cost model: unit costs, tile values or one cost per tile typed by the user
+fn read_cost_model(tiles: usize) -> Option<CostModel> {
+    let models = vec!["Every move costs 1", "Moving tile t costs t", "Cost table"];
+    let model = Select::with_theme(&ColorfulTheme::default())
+        .items(&models)
+        .default(1)
+        .interact_on(&Term::stderr())
+        .expect("failed");
+    match model {
+        0 => Some(CostModel::Unit),
+        1 => Some(CostModel::TileValue),
+        _ => {
+            let text: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!("Costs of tiles 1..{}", tiles - 1))
+                .interact_text()
+                .expect("failed");
+            let mut table: Vec<u32> = vec![0];
+            table.extend(text.split_whitespace().filter_map(|cost| cost.parse::<u32>().ok()));
+            if table.len() != tiles || table[1..].contains(&0) {
+                println!("A cost table needs one positive cost for every tile.");
+                return None;
+            }
+            Some(CostModel::Table(table))
+        }
+    }
+}
+
+fn weighted_tiles() {
+    let (rows, cols) = read_dimensions(3, 3);
+    let Some(costs) = read_cost_model(rows * cols) else { return };
+    let n: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Random moves from goal")
+        .default(30)
+        .interact_text()
+        .expect("failed");
+
+    let start = sliding::n_random_moves_from_goal(rows, cols, n);
+    let goal = Board::goal(rows, cols);
+    print!("{}", start);
+    let manhattan = |board: &Board| heuristics::weighted_manhattan_distance_to(board, &goal, &costs);
+    let show = |name: &str, solution: Option<Solution>, now: Instant| {
+        if let Some(solution) = &solution {
+            println!("{}: path cost = {}", name, costs.path_cost(&start, &solution.moves));
+        }
+        report(name, solution, now);
+    };
+    if rows * cols <= 9 {
+        let now = Instant::now();
+        show("A* weighted Manhattan", search::a_star_search_with_costs(&start, &goal, &manhattan, &costs), now);
+    }
+    let now = Instant::now();
+    show("IDA* weighted Manhattan", search::ida_star_search_with_costs(&start, &goal, &manhattan, &costs, &mut |_, _| {}), now);
+    if rows * cols <= 16 {
+        // Groups of five tiles keep every database small enough to build on the spot
+        let tiles: Vec<u16> = (1..(rows * cols) as u16).collect();
+        let databases = match tiles.chunks(5).map(|pattern| PatternDatabase::build_weighted(&goal, pattern, &costs)).collect::<io::Result<Vec<_>>>() {
+            Ok(databases) => AdditivePatternDatabase::new(databases),
+            Err(error) => {
+                println!("Could not build the pattern databases: {}", error);
+                return;
+            }
+        };
+        let now = Instant::now();
+        let heuristic = |board: &Board| databases.estimate(board);
+        show("IDA* weighted pattern databases", search::ida_star_search_with_costs(&start, &goal, &heuristic, &costs, &mut |_, _| {}), now);
+    }
+    println!("\n");
+}
+
+fn inconsistent_heuristics() {
+    let n: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Random moves from goal")
+        .default(50)
+        .interact_text()
+        .expect("failed");
+
+    let start = sliding::n_random_moves_from_goal(4, 4, n);
+    let goal = Board::goal(4, 4);
+    print!("{}", start);
+    // Two 5-5-5 partitions, by rows and by columns. Picking one of them per board keeps the
+    // heuristic admissible but makes it inconsistent.
+    let by_rows = AdditivePatternDatabase::new([[1, 2, 3, 4, 5], [6, 7, 8, 9, 10], [11, 12, 13, 14, 15]].iter().map(|pattern| PatternDatabase::build(&goal, pattern)).collect());
+    let by_columns = AdditivePatternDatabase::new([[1, 5, 9, 13, 2], [6, 10, 14, 3, 7], [11, 15, 4, 8, 12]].iter().map(|pattern| PatternDatabase::build(&goal, pattern)).collect());
+    let consistent = |board: &Board| by_rows.estimate(board);
+    let inconsistent = |board: &Board| {
+        let mut hasher = DefaultHasher::new();
+        board.hash(&mut hasher);
+        if hasher.finish().is_multiple_of(2) {
+            by_rows.estimate(board)
+        } else {
+            by_columns.estimate(board)
+        }
+    };
+    let plain = SearchOptions { bpmx: false, detect_inconsistency: true, ..SearchOptions::default() };
+    let bpmx = SearchOptions { bpmx: true, detect_inconsistency: true, ..SearchOptions::default() };
+    let heuristics: [&dyn Fn(&Board) -> u32; 3] = [&consistent, &inconsistent, &inconsistent];
+    let runs = [("consistent", plain), ("inconsistent", plain), ("inconsistent + BPMX", bpmx)];
+    for ((name, options), heuristic) in runs.into_iter().zip(heuristics) {
+        let now = Instant::now();
+        let solution = search::ida_star_search_with_options(&start, &goal, heuristic, &CostModel::Unit, &options, &mut |_, _| {});
+        report_inconsistency(&format!("IDA* {}", name), solution, now);
+        let now = Instant::now();
+        let solution = search::a_star_search_with_options(&start, &goal, heuristic, &CostModel::Unit, &options);
+        report_inconsistency(&format!("A* {}", name), solution, now);
+    }
+    println!("\n");
+}
+
+fn report_inconsistency(name: &str, solution: Option<Solution>, now: Instant) {
+    if let Some(solution) = &solution {
+        let stats = solution.stats;
+        println!("{}: inconsistent edges = {}, reopened = {}, pathmax updates = {}", name, stats.inconsistent_edges, stats.reopened, stats.pathmax_updates);
+    }
+    report(name, solution, now);
+}
+
+fn dual_lookups() {
+    let n: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Random moves from goal")
+        .default(60)
+        .interact_text()
+        .expect("failed");
+
+    let start = sliding::n_random_moves_from_goal(4, 4, n);
+    let goal = Board::goal(4, 4);
+    print!("{}", start);
+    let databases = AdditivePatternDatabase::new([[1, 2, 3, 4, 5], [6, 7, 8, 9, 10], [11, 12, 13, 14, 15]].iter().map(|pattern| PatternDatabase::build(&goal, pattern)).collect());
+    let table = |board: &Board| databases.estimate(board);
+    let lookups = SymmetricLookups::new(&goal, &table);
+    let runs = [
+        ("regular", Selection::Regular, false),
+        ("max(regular, dual)", Selection::MaxRegularDual, false),
+        ("max(regular, dual, reflected)", Selection::MaxAll, false),
+        ("random + BPMX", Selection::Random, true),
+    ];
+    for (name, selection, bpmx) in runs {
+        let options = SearchOptions { bpmx, ..SearchOptions::default() };
+        let now = Instant::now();
+        let heuristic = |board: &Board| lookups.estimate(board, selection);
+        report(&format!("IDA* {}", name), search::ida_star_search_with_options(&start, &goal, &heuristic, &CostModel::Unit, &options, &mut |_, _| {}), now);
+    }
+    println!("\n");
+}
+
+fn tie_breaking() {
+    let n: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Random moves from goal")
+        .default(40)
+        .interact_text()
+        .expect("failed");
+
+    let start = sliding::n_random_moves_from_goal(4, 4, n);
+    let goal = Board::goal(4, 4);
+    print!("{}", start);
+    let heuristic = |board: &Board| manhattan_distance(board) + heuristics::linear_conflict_to(board, &goal);
+    let rules = [("larger g", TieBreaking::LargerG), ("LIFO", TieBreaking::Lifo), ("FIFO", TieBreaking::Fifo)];
+    for (name, tie_breaking) in rules {
+        let options = SearchOptions { tie_breaking, ..SearchOptions::default() };
+        let now = Instant::now();
+        report(&format!("A* {}", name), search::a_star_search_with_options(&start, &goal, &heuristic, &CostModel::Unit, &options), now);
+    }
+    println!("\n");
+}
+
+fn memory_bounded_search() {
+    let n: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Random moves from goal")
+        .default(40)
+        .interact_text()
+        .expect("failed");
+    let limit: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Memory limit (nodes)")
+        .default(100000)
+        .interact_text()
+        .expect("failed");
+
+    let start = sliding::n_random_moves_from_goal(4, 4, n);
+    let goal = Board::goal(4, 4);
+    print!("{}", start);
+    let heuristic = |board: &Board| manhattan_distance(board) + heuristics::linear_conflict_to(board, &goal);
+    let now = Instant::now();
+    report("A*", search::a_star_search(&start, &goal, &heuristic), now);
+    let now = Instant::now();
+    report("SMA*", memory_bounded::sma_star_search(&start, &goal, &heuristic, limit), now);
+    let now = Instant::now();
+    report("Frontier A*", memory_bounded::frontier_a_star_search(&start, &goal, &heuristic, limit), now);
+    println!("\n");
+}
+
+fn partial_expansion() {
+    let n: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Random moves from goal")
+        .default(50)
+        .interact_text()
+        .expect("failed");
+
+    let start = sliding::n_random_moves_from_goal(4, 4, n);
+    let goal = Board::goal(4, 4);
+    print!("{}", start);
+    let now = Instant::now();
+    report("A* Manhattan", search::a_star_search(&start, &goal, &manhattan_distance), now);
+    let operators = ManhattanOperators::new(&goal);
+    let now = Instant::now();
+    report("EPEA* Manhattan", epea::epea_star_search(&start, &goal, &operators), now);
+    let databases = AdditivePatternDatabase::new([[1, 2, 3, 4, 5], [6, 7, 8, 9, 10], [11, 12, 13, 14, 15]].iter().map(|pattern| PatternDatabase::build(&goal, pattern)).collect());
+    let now = Instant::now();
+    report("A* pattern databases", search::a_star_search(&start, &goal, &|board| databases.estimate(board)), now);
+    let now = Instant::now();
+    report("EPEA* pattern databases", epea::epea_star_search(&start, &goal, &databases), now);
+    println!("\n");
+}
+
+fn external_memory_bfs() {
+    let (rows, cols) = read_dimensions(3, 4);
+    let goal = Board::goal(rows, cols);
+    let parse = |text: &str| -> Result<Vec<u16>, String> {
+        let pattern = text
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|tile| !tile.is_empty())
+            .map(|tile| tile.parse::<u16>())
+            .collect::<Result<Vec<u16>, _>>()
+            .map_err(|_| "tiles must be numbers".to_string())?;
+        external_bfs::validate_pattern(&goal, &pattern).map_err(|error| error.to_string())?;
+        Ok(pattern)
+    };
+    let pattern: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Tracked tiles (empty for every tile)")
+        .allow_empty(true)
+        .validate_with(|text: &String| parse(text).map(|_| ()))
+        .interact_text()
+        .expect("failed");
+    let pattern = parse(&pattern).expect("pattern was validated");
+    let buffer: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("States sorted in memory at once")
+        .default(1 << 24)
+        .interact_text()
+        .expect("failed");
+
+    let now = Instant::now();
+    let result = external_bfs::external_bfs(&goal, &pattern, Path::new("bfs"), buffer, &mut |depth, count| {
+        println!("depth {:>3}: {}, elapsed: {:.2?}", depth, count, now.elapsed());
+    });
+    let distribution = match result {
+        Ok(distribution) => distribution,
+        Err(error) => {
+            println!("Search failed: {}", error);
+            return;
+        }
+    };
+    println!("states = {}, diameter = {}, antipodes = {}", distribution.states(), distribution.diameter(), distribution.antipodes.len());
+    for cells in distribution.antipodes.iter().take(10) {
+        for row in cells.chunks(cols) {
+            let tiles: Vec<String> = row.iter().map(|tile| tile.map_or("*".to_string(), |tile| tile.to_string())).collect();
+            println!("[{}]", tiles.join(", "));
+        }
+        println!();
+    }
+    let elapsed = now.elapsed();
+    println!("Elapsed: {:.2?}", elapsed);
+    println!("\n");
+}
+
+fn generate_instances() {
+    let (rows, cols) = read_dimensions(4, 4);
+    let kinds = vec!["Distance range", "Hard (deepest of n random boards)"];
+    let kind = Select::with_theme(&ColorfulTheme::default())
+        .items(&kinds)
+        .default(0)
+        .interact_on_opt(&Term::stderr())
+        .expect("failed");
+    let count: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Number of boards")
+        .default(5)
+        .interact_text()
+        .expect("failed");
+    let seed: u64 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Seed")
+        .default(0)
+        .interact_text()
+        .expect("failed");
+
+    let mut generator = Generator::new(rows, cols, seed);
+    // Either a distance range, or the number of random boards to take the deepest of
+    let (range, samples) = match kind {
+        Some(0) => {
+            let min: u32 = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Minimum distance")
+                .default(40)
+                .interact_text()
+                .expect("failed");
+            let max: u32 = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Maximum distance")
+                .default(min)
+                .interact_text()
+                .expect("failed");
+            (Some((min, max)), 0)
+        }
+        Some(_) => {
+            let samples: usize = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Random boards per instance")
+                .default(10)
+                .interact_text()
+                .expect("failed");
+            (None, samples)
+        }
+        None => return,
+    };
+    let now = Instant::now();
+    for _ in 0..count {
+        let instance = match range {
+            Some((min, max)) => generator.with_distance(min, max),
+            None => Some(generator.hard(samples)),
+        };
+        match instance {
+            Some((board, distance)) => {
+                let tiles: Vec<String> = board.tiles().iter().map(|tile| tile.to_string()).collect();
+                println!("distance = {}: {}", distance, tiles.join(" "));
+            }
+            None => {
+                println!("No board found in that range.");
+                break;
+            }
+        }
+    }
+    let elapsed = now.elapsed();
+    println!("Elapsed: {:.2?}", elapsed);
+    println!("\n");
+}
+
+fn heuristic_quality_report() {
+    let (rows, cols) = read_dimensions(4, 4);
+    let count: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Number of boards")
+        .default(50)
+        .interact_text()
+        .expect("failed");
+    let min: u32 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Minimum distance")
+        .default(20)
+        .interact_text()
+        .expect("failed");
+    let max: u32 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Maximum distance")
+        .default(40)
+        .interact_text()
+        .expect("failed");
+    let searches = vec!["Skip IDA*", "Count IDA* nodes"];
+    let run_ida = Select::with_theme(&ColorfulTheme::default())
+        .items(&searches)
+        .default(0)
+        .interact_on_opt(&Term::stderr())
+        .expect("failed")
+        == Some(1);
+    let prefix: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("CSV file prefix")
+        .default("heuristic_report".to_string())
+        .interact_text()
+        .expect("failed");
+
+    let now = Instant::now();
+    let goal = Board::goal(rows, cols);
+    // Spread the boards evenly over the distances of the range
+    let mut generator = Generator::new(rows, cols, 0);
+    let mut instances = vec![];
+    for i in 0..count {
+        let distance = min + (i as u32) % (max.saturating_sub(min) + 1);
+        if let Some(instance) = generator.with_distance(distance, distance) {
+            instances.push(instance);
+        }
+    }
+    println!("{} boards generated, elapsed: {:.2?}", instances.len(), now.elapsed());
+    if instances.is_empty() {
+        return;
+    }
+
+    let tiles: Vec<u16> = (1..(rows * cols) as u16).collect();
+    let databases = AdditivePatternDatabase::new(tiles.chunks(5).map(|pattern| PatternDatabase::build(&goal, pattern)).collect());
+    let walking_distance = WalkingDistance::new(&goal);
+    let linear_conflict = |board: &Board| manhattan_distance(board) + heuristics::linear_conflict_to(board, &goal);
+    let walking = |board: &Board| walking_distance.estimate(board);
+    let walking_conflict = |board: &Board| walking_distance.estimate(board) + heuristics::linear_conflict_to(board, &goal);
+    let pattern = |board: &Board| databases.estimate(board);
+    let names = ["Manhattan", "Manhattan + linear conflict", "Walking distance", "Walking distance + linear conflict", "Pattern databases"];
+    let heuristics: [&dyn Fn(&Board) -> u32; 5] = [&manhattan_distance, &linear_conflict, &walking, &walking_conflict, &pattern];
+    let mut reports: Vec<HeuristicReport> = vec![];
+    for (name, heuristic) in names.into_iter().zip(heuristics) {
+        let report = heuristic_report::analyse(name, heuristic, &instances, &goal, run_ida);
+        let expanded = report.ida_expanded.map_or("-".to_string(), |expanded| expanded.to_string());
+        println!(
+            "{}: mean h/h* = {:.3}, correlation = {:.3}, overestimates = {}, {:.0} ns per call, IDA* expanded = {}",
+            name, report.mean_ratio, report.correlation, report.overestimates, report.nanos_per_call, expanded
+        );
+        reports.push(report);
+    }
+    let written = std::fs::File::create(format!("{}_summary.csv", prefix))
+        .and_then(|mut file| heuristic_report::write_summary_csv(&mut file, &reports))
+        .and_then(|_| std::fs::File::create(format!("{}_errors.csv", prefix)))
+        .and_then(|mut file| heuristic_report::write_errors_csv(&mut file, &reports));
+    match written {
+        Ok(()) => println!("Written {}_summary.csv and {}_errors.csv", prefix, prefix),
+        Err(error) => println!("Could not write the CSV files: {}", error),
+    }
+    let elapsed = now.elapsed();
+    println!("Elapsed: {:.2?}", elapsed);
+    println!("\n");
+}
+
+fn combine_heuristics() {
+    let (rows, cols) = read_dimensions(4, 4);
+    let goal = Board::goal(rows, cols);
+    // Pattern databases over consecutive groups of at most five tiles
+    let tiles: Vec<u16> = (1..(rows * cols) as u16).collect();
+    let groups: Vec<Vec<u16>> = tiles.chunks(5).map(|group| group.to_vec()).collect();
+    let mut names = vec!["Manhattan".to_string(), "Manhattan + linear conflict".to_string(), "Walking distance".to_string()];
+    names.extend(groups.iter().map(|group| format!("PDB {:?}", group)));
+    // A model saved by "Train learned heuristic" for this board size
+    let model_path = format!("learned_{}x{}.txt", rows, cols);
+    let learned = LearnedHeuristic::load(Path::new(&model_path)).ok().filter(|learned| *learned.goal() == goal);
+    if learned.is_some() {
+        names.push(format!("Learned ({})", model_path));
+    }
+    let mut learned = learned;
+    let chosen = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Heuristics (space to select)")
+        .items(&names)
+        .interact_on_opt(&Term::stderr())
+        .expect("failed");
+    let Some(chosen) = chosen.filter(|chosen| !chosen.is_empty()) else {
+        return;
+    };
+    let combinators = vec!["Max", "Sum"];
+    let Some(combinator) = Select::with_theme(&ColorfulTheme::default())
+        .items(&combinators)
+        .default(0)
+        .interact_on_opt(&Term::stderr())
+        .expect("failed")
+    else {
+        return;
+    };
+    let weight: f64 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Weight")
+        .default(1.0)
+        .interact_text()
+        .expect("failed");
+    let algorithms = vec!["A*", "IDA*"];
+    let Some(algorithm) = Select::with_theme(&ColorfulTheme::default())
+        .items(&algorithms)
+        .default(1)
+        .interact_on_opt(&Term::stderr())
+        .expect("failed")
+    else {
+        return;
+    };
+    let n: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Random moves from goal")
+        .default(50)
+        .interact_text()
+        .expect("failed");
+
+    let parts: Vec<Box<dyn Heuristic>> = chosen
+        .into_iter()
+        .map(|i| -> Box<dyn Heuristic> {
+            match i {
+                0 => Box::new(Manhattan::new(&goal)),
+                1 => Box::new(LinearConflict::new(&goal)),
+                2 => Box::new(WalkingDistance::new(&goal)),
+                i if i < 3 + groups.len() => Box::new(PatternDatabase::build(&goal, &groups[i - 3])),
+                _ => Box::new(learned.take().expect("the learned model is listed once")),
+            }
+        })
+        .collect();
+    let mut heuristic: Box<dyn Heuristic> = match (parts.len(), combinator) {
+        (1, _) => parts.into_iter().next().expect("one heuristic is chosen"),
+        (_, 0) => Box::new(MaxOf::new(parts)),
+        _ => Box::new(SumOf::new(parts)),
+    };
+    if weight != 1.0 {
+        heuristic = Box::new(Scaled::new(weight, heuristic));
+    }
+    println!("{}: admissible = {}, additive = {}", heuristic.name(), heuristic.is_admissible(), heuristic.additive_tiles().is_some());
+
+    let start = sliding::n_random_moves_from_goal(rows, cols, n);
+    print!("{}", start);
+    let estimate = |board: &Board| heuristic.estimate(board);
+    let now = Instant::now();
+    match algorithm {
+        0 => report("A*", search::a_star_search(&start, &goal, &estimate), now),
+        _ => report("IDA*", search::ida_star_search(&start, &goal, &estimate), now),
+    }
+    println!("\n");
+}
+
+fn train_learned_heuristic() {
+    let (rows, cols) = read_dimensions(3, 3);
+    let count: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Training boards")
+        .default(4000)
+        .interact_text()
+        .expect("failed");
+    let min: u32 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Minimum distance")
+        .default(0)
+        .interact_text()
+        .expect("failed");
+    let max: u32 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Maximum distance")
+        .default(31)
+        .interact_text()
+        .expect("failed");
+    let defaults = Training::default();
+    let epochs: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Epochs")
+        .default(defaults.epochs)
+        .interact_text()
+        .expect("failed");
+    let quantile: f64 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Share of held-out boards not to overestimate")
+        .default(defaults.quantile)
+        .interact_text()
+        .expect("failed");
+    let path: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Model file")
+        .default(format!("learned_{}x{}.txt", rows, cols))
+        .interact_text()
+        .expect("failed");
+
+    let now = Instant::now();
+    let goal = Board::goal(rows, cols);
+    let mut generator = Generator::new(rows, cols, 0);
+    let mut instances = vec![];
+    for i in 0..count {
+        let distance = min + (i as u32) % (max.saturating_sub(min) + 1);
+        if let Some(instance) = generator.with_distance(distance, distance) {
+            instances.push(instance);
+        }
+    }
+    println!("{} boards solved, elapsed: {:.2?}", instances.len(), now.elapsed());
+    let training = Training { epochs, quantile, ..defaults };
+    let (learned, evaluation) = LearnedHeuristic::train(&goal, &instances, &training, &mut |epoch, error| {
+        if epoch % 10 == 0 || epoch + 1 == epochs {
+            println!("epoch {:>3}: mean squared error = {:.3}", epoch, error);
+        }
+    });
+    println!(
+        "training mean squared error = {:.3}, held-out mean absolute error = {:.3}, offset = {:.3}, still overestimated = {:.1}%",
+        evaluation.train_error,
+        evaluation.mean_absolute_error,
+        evaluation.offset,
+        100.0 * evaluation.overestimated
+    );
+    match learned.save(Path::new(&path)) {
+        Ok(()) => println!("Model written to {}", path),
+        Err(error) => println!("Could not write the model: {}", error),
+    }
+    let elapsed = now.elapsed();
+    println!("Elapsed: {:.2?}", elapsed);
+    println!("\n");
+}
+
+fn play_puzzle() {
+    let sources = vec!["8 puzzle, shuffled", "15 puzzle from n moves", "Load from file"];
+    let Some(source) = Select::with_theme(&ColorfulTheme::default())
+        .items(&sources)
+        .default(0)
+        .interact_on_opt(&Term::stderr())
+        .expect("failed")
+    else {
+        return;
+    };
+    let start = match source {
+        0 => sliding::random_board(3, 3),
+        1 => {
+            let n: usize = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Random moves from the goal")
+                .default(30)
+                .interact_text()
+                .expect("failed");
+            sliding::n_random_moves_from_goal(4, 4, n)
+        }
+        _ => {
+            let path: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Board file (3x3 or 4x4, one row per line)")
+                .interact_text()
+                .expect("failed");
+            match play::load_board(Path::new(&path)) {
+                Ok(board) => board,
+                Err(error) => {
+                    println!("Could not load the board: {}", error);
+                    println!("\n");
+                    return;
+                }
+            }
+        }
+    };
+    println!("Solving the start board...");
+    let now = Instant::now();
+    let mut game = Game::new(start);
+    println!("optimum = {}, elapsed: {:.2?}", game.optimum(), now.elapsed());
+    play::run(&Term::stdout(), &mut game).expect("failed");
+    println!("\n");
+}
+
+fn search_tree_to_dot() {
+    let algorithms = vec!["A*", "IDA*"];
+    let Some(algorithm) = Select::with_theme(&ColorfulTheme::default())
+        .items(&algorithms)
+        .default(0)
+        .interact_on_opt(&Term::stderr())
+        .expect("failed")
+    else {
+        return;
+    };
+    let n: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Random moves from goal (8 puzzle)")
+        .default(8)
+        .interact_text()
+        .expect("failed");
+    let cap: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Node cap")
+        .default(500)
+        .interact_text()
+        .expect("failed");
+    let path: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Output file")
+        .default("search_tree.dot".to_string())
+        .interact_text()
+        .expect("failed");
+
+    let start = sliding::n_random_moves_from_goal(3, 3, n);
+    let goal = Board::goal(3, 3);
+    print!("{}", start);
+    let now = Instant::now();
+    let heuristic = |board: &Board| manhattan_distance_to(board, &goal);
+    let tree = match algorithm {
+        0 => search_tree::a_star_tree(&start, &goal, &heuristic, cap),
+        _ => search_tree::ida_star_tree(&start, &goal, &heuristic, cap),
+    };
+    let expanded = tree.nodes.iter().filter(|node| node.kind == NodeKind::Expanded).count();
+    let duplicates = tree.nodes.iter().filter(|node| node.kind == NodeKind::Duplicate).count();
+    println!("nodes = {}, expanded = {}, duplicates = {}", tree.nodes.len(), expanded, duplicates);
+    if tree.solution.is_empty() {
+        println!("no solution within the node cap");
+    } else {
+        println!("path length = {}", tree.moves().len());
+    }
+    if tree.truncated {
+        println!("truncated at {} nodes", cap);
+    }
+    let written = std::fs::File::create(&path).and_then(|file| {
+        let mut writer = io::BufWriter::new(file);
+        search_tree::write_dot(&mut writer, &tree)?;
+        writer.flush()
+    });
+    match written {
+        Ok(()) => println!("Graph written to {}, render it with: dot -Tsvg {} -o search_tree.svg", path, path),
+        Err(error) => println!("Could not write the graph: {}", error),
+    }
+    let elapsed = now.elapsed();
+    println!("Elapsed: {:.2?}", elapsed);
+    println!("\n");
+}
+
+fn observe_search() {
+    let algorithms = vec!["A*", "IDA*"];
+    let Some(algorithm) = Select::with_theme(&ColorfulTheme::default())
+        .items(&algorithms)
+        .default(1)
+        .interact_on_opt(&Term::stderr())
+        .expect("failed")
+    else {
+        return;
+    };
+    let n: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Random moves from goal")
+        .default(40)
+        .interact_text()
+        .expect("failed");
+    let names = vec!["Progress bar", "Event histogram", "JSON-lines trace"];
+    let Some(chosen) = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Observers (space to select)")
+        .items(&names)
+        .defaults(&[true, true, false])
+        .interact_on_opt(&Term::stderr())
+        .expect("failed")
+    else {
+        return;
+    };
+    let trace_path = if chosen.contains(&2) {
+        let path: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Trace file")
+            .default("trace.jsonl".to_string())
+            .interact_text()
+            .expect("failed");
+        Some(path)
+    } else {
+        None
+    };
+    let mut trace = match trace_path.as_ref().map(std::fs::File::create).transpose() {
+        Ok(file) => file.map(|file| JsonTrace::new(io::BufWriter::new(file))),
+        Err(error) => {
+            println!("Could not create the trace: {}", error);
+            return;
+        }
+    };
+
+    let start = sliding::n_random_moves_from_goal(4, 4, n);
+    let goal = Board::goal(4, 4);
+    print!("{}", start);
+    let heuristic = |board: &Board| manhattan_distance(board) + heuristics::linear_conflict_to(board, &goal);
+    let mut progress_bar = chosen.contains(&0).then(|| ProgressBar::new(Term::stderr(), None));
+    let mut histogram = chosen.contains(&1).then(EventHistogram::default);
+    let mut observers: Vec<&mut dyn SearchObserver> = vec![];
+    if let Some(progress_bar) = progress_bar.as_mut() {
+        observers.push(progress_bar);
+    }
+    if let Some(histogram) = histogram.as_mut() {
+        observers.push(histogram);
+    }
+    if let Some(trace) = trace.as_mut() {
+        observers.push(trace);
+    }
+    let mut observer = Broadcast(observers);
+    let now = Instant::now();
+    let options = SearchOptions::default();
+    let solution = match algorithm {
+        0 => search::a_star_search_observed(&start, &goal, &heuristic, &CostModel::Unit, &options, &mut observer),
+        _ => search::ida_star_search_observed(&start, &goal, &heuristic, &CostModel::Unit, &options, &mut observer),
+    };
+    report(algorithms[algorithm], solution, now);
+    if let Some(histogram) = histogram {
+        let (expanded, generated, duplicates) = histogram.totals();
+        println!("events: expanded = {}, generated = {}, duplicates = {}", expanded, generated, duplicates);
+        for (bound, expanded) in &histogram.iterations {
+            println!("iteration with bound {} after {} expansions", bound, expanded);
+        }
+        histogram.write_csv(&mut io::stdout()).expect("failed");
+    }
+    if let (Some(trace), Some(path)) = (trace, trace_path) {
+        match trace.finish() {
+            Ok(_) => println!("Trace written to {}", path),
+            Err(error) => println!("Could not write the trace: {}", error),
+        }
+    }
+    println!("\n");
+}
+
+fn resumable_ida() {
+    let file: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Checkpoint file")
+        .default("ida_checkpoint.txt".to_string())
+        .interact_text()
+        .expect("failed");
+    let file = Path::new(&file);
+    let (start, goal) = match Checkpoint::load(file) {
+        Ok(checkpoint) => {
+            println!(
+                "Resuming at bound {}, depth {}, expanded = {}",
+                checkpoint.bound,
+                checkpoint.path.len(),
+                checkpoint.stats.expanded
+            );
+            (checkpoint.start, checkpoint.goal)
+        }
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            let sources = vec!["15 puzzle from n moves", "15 puzzle, shuffled", "24 puzzle, seeded"];
+            let Some(source) = Select::with_theme(&ColorfulTheme::default())
+                .items(&sources)
+                .default(0)
+                .interact_on_opt(&Term::stderr())
+                .expect("failed")
+            else {
+                return;
+            };
+            match source {
+                0 => {
+                    let n: usize = Input::with_theme(&ColorfulTheme::default())
+                        .with_prompt("Random moves from goal")
+                        .default(60)
+                        .interact_text()
+                        .expect("failed");
+                    (sliding::n_random_moves_from_goal(4, 4, n), Board::goal(4, 4))
+                }
+                1 => (sliding::random_board(4, 4), Board::goal(4, 4)),
+                _ => {
+                    let seed: u64 = Input::with_theme(&ColorfulTheme::default())
+                        .with_prompt("Seed")
+                        .default(0)
+                        .interact_text()
+                        .expect("failed");
+                    (puzzle24::seeded_state(seed), puzzle24::goal())
+                }
+            }
+        }
+        Err(error) => {
+            println!("Could not read the checkpoint: {}", error);
+            return;
+        }
+    };
+    let interval: u64 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Seconds between checkpoints")
+        .default(60)
+        .interact_text()
+        .expect("failed");
+    print!("{}", start);
+
+    // The 15-puzzle uses Manhattan distance with linear conflicts, larger boards the 6-6-6-6
+    // pattern databases of the 24-puzzle
+    let databases = if goal.tiles().len() > 16 {
+        let dir: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Pattern database directory")
+            .default("pdb".to_string())
+            .interact_text()
+            .expect("failed");
+        match puzzle24::pattern_databases(&goal, Path::new(&dir), &mut |i, pattern| println!("Loading pattern database {}/4 for tiles {:?}", i + 1, pattern)) {
+            Ok(databases) => Some(databases),
+            Err(error) => {
+                println!("Could not load pattern databases: {}", error);
+                return;
+            }
+        }
+    } else {
+        None
+    };
+    let heuristic = |board: &Board| match &databases {
+        Some(databases) => databases.estimate(board),
+        None => manhattan_distance(board) + heuristics::linear_conflict_to(board, &goal),
+    };
+    let now = Instant::now();
+    let mut progress_bar = ProgressBar::new(Term::stderr(), None);
+    let solution = checkpoint::resumable_ida_star_search(&start, &goal, &heuristic, &CostModel::Unit, &SearchOptions::default(), SaveOptions { file, interval: Duration::from_secs(interval) }, &mut progress_bar);
+    match solution {
+        Ok(solution) => {
+            if let Some(solution) = &solution {
+                println!("moves = {}", moves_to_string(&solution.moves));
+            }
+            report("Resumable IDA*", solution, now);
+        }
+        Err(error) => println!("Search stopped: {}", error),
+    }
+    println!("\n");
+}
+
+fn batch_solve() {
+    let path: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Instance file (`<id> <tiles>`, or `seed <s>` / `seed <s> moves <n>` per line)")
+        .interact_text()
+        .expect("failed");
+    let (rows, cols) = read_dimensions(4, 4);
+    let instances = match batch::load_instances(Path::new(&path), rows, cols) {
+        Ok(instances) => instances,
+        Err(error) => {
+            println!("Could not load instances: {}", error);
+            return;
+        }
+    };
+    let algorithms = vec!["A*", "IDA*"];
+    let Some(algorithm) = Select::with_theme(&ColorfulTheme::default())
+        .items(&algorithms)
+        .default(1)
+        .interact_on_opt(&Term::stderr())
+        .expect("failed")
+    else {
+        return;
+    };
+    let threads: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Threads")
+        .default(thread::available_parallelism().map_or(1, |threads| threads.get()))
+        .interact_text()
+        .expect("failed");
+    let max_expanded: usize = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Expansion limit per instance (0 for none)")
+        .default(0)
+        .interact_text()
+        .expect("failed");
+    let seconds: f64 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Time limit per instance in seconds (0 for none)")
+        .default(0.0)
+        .validate_with(|seconds: &f64| if *seconds == 0.0 || Duration::try_from_secs_f64(*seconds).is_ok() { Ok(()) } else { Err("expected 0 or a positive, finite number of seconds") })
+        .interact_text()
+        .expect("failed");
+    let output: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Output file (.csv or .jsonl)")
+        .default("results.csv".to_string())
+        .interact_text()
+        .expect("failed");
+
+    let options = BatchOptions {
+        algorithm: if algorithm == 0 { Algorithm::AStar } else { Algorithm::IdaStar },
+        max_expanded: (max_expanded > 0).then_some(max_expanded),
+        time_limit: (seconds > 0.0).then(|| Duration::try_from_secs_f64(seconds).ok()).flatten(),
+        threads,
+    };
+    let now = Instant::now();
+    let summary = batch::run_batch(&instances, Path::new(&output), &options, &mut |result| {
+        let length = result.moves.as_ref().map_or("-".to_string(), |moves| moves.len().to_string());
+        println!("instance {}: {}, path length = {}, expanded = {}, elapsed: {:.2?}", result.id, result.status.name(), length, result.stats.expanded, result.elapsed);
+    });
+    match summary {
+        Ok(summary) => println!("{} solved, {} unsolved, {} already solved in {}", summary.solved, summary.unsolved, summary.skipped, output),
+        Err(error) => println!("Batch stopped: {}", error),
+    }
+    let elapsed = now.elapsed();
+    println!("Elapsed: {:.2?}", elapsed);
+    println!("\n");
+}